@@ -1,7 +1,11 @@
 use std::path::PathBuf;
+use std::str::FromStr;
 use strum_macros::Display;
 
+use ahash::AHashMap;
 use clap::{Parser, Subcommand, ValueEnum};
+use sqruff_lib::templaters::TEMPLATERS;
+use sqruff_lib_core::dialects::init::{dialect_readout, DialectKind};
 
 use crate::github_action::is_in_github_action;
 
@@ -15,6 +19,75 @@ pub(crate) struct Cli {
     /// Path to a configuration file.
     #[arg(long, global = true)]
     pub config: Option<String>,
+    /// The dialect of the SQL, overriding the config files.
+    #[arg(long, global = true)]
+    pub dialect: Option<String>,
+    /// Only run these rules, overriding the config files.
+    #[arg(long, global = true, value_delimiter = ',')]
+    pub rules: Option<Vec<String>>,
+    /// Don't run these rules, overriding the config files.
+    #[arg(long, global = true, value_delimiter = ',')]
+    pub exclude_rules: Option<Vec<String>>,
+    /// The templater to use, overriding the config files.
+    #[arg(long, global = true)]
+    pub templater: Option<String>,
+    /// Ignore errors of these categories, overriding the config files.
+    #[arg(long, global = true, value_delimiter = ',')]
+    pub ignore: Option<Vec<IgnoreCategory>>,
+    /// The maximum line length, overriding the config files.
+    #[arg(long, global = true)]
+    pub max_line_length: Option<u32>,
+}
+
+impl Cli {
+    /// The core config values given on the command line.
+    pub(crate) fn config_overrides(&self) -> Result<AHashMap<String, String>, String> {
+        let mut overrides = AHashMap::new();
+
+        if let Some(dialect) = &self.dialect {
+            if DialectKind::from_str(dialect).is_err() {
+                return Err(format!(
+                    "Unknown dialect '{dialect}'. Available dialects: {}",
+                    dialect_readout().join(", ")
+                ));
+            }
+            overrides.insert("dialect".to_owned(), dialect.clone());
+        }
+        if let Some(templater) = &self.templater {
+            if !TEMPLATERS.iter().any(|it| it.name() == templater) {
+                return Err(format!(
+                    "Unknown templater '{templater}'. Available templaters: {}",
+                    TEMPLATERS
+                        .iter()
+                        .map(|it| it.name())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+            overrides.insert("templater".to_owned(), templater.clone());
+        }
+        if let Some(rules) = &self.rules {
+            overrides.insert("rules".to_owned(), rules.join(","));
+        }
+        if let Some(exclude_rules) = &self.exclude_rules {
+            overrides.insert("exclude_rules".to_owned(), exclude_rules.join(","));
+        }
+        if let Some(ignore) = &self.ignore {
+            overrides.insert(
+                "ignore".to_owned(),
+                ignore
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(","),
+            );
+        }
+        if let Some(max_line_length) = self.max_line_length {
+            overrides.insert("max_line_length".to_owned(), max_line_length.to_string());
+        }
+
+        Ok(overrides)
+    }
 }
 
 #[derive(Debug, Subcommand)]
@@ -23,6 +96,12 @@ pub(crate) enum Commands {
     Lint(LintArgs),
     #[command(name = "fix", about = "fix files")]
     Fix(FixArgs),
+    #[command(name = "parse", about = "print the parse tree of files")]
+    Parse(ParseArgs),
+    #[command(name = "rules", about = "list the available rules")]
+    Rules(ListArgs),
+    #[command(name = "dialects", about = "list the available dialects")]
+    Dialects(ListArgs),
     #[command(name = "lsp", about = "Run an LSP server")]
     Lsp,
 }
@@ -33,6 +112,23 @@ pub(crate) struct LintArgs {
     pub paths: Vec<PathBuf>,
     #[arg(default_value_t, short, long)]
     pub format: Format,
+    /// Only lint files changed since the merge base with this git ref, and only
+    /// report violations on the changed lines.
+    #[arg(long, value_name = "GIT_REF")]
+    pub diff_base: Option<String>,
+    /// Don't report the violations recorded in this baseline file.
+    #[arg(long, value_name = "FILE")]
+    pub baseline: Option<PathBuf>,
+    /// Record the current violations in a baseline file, instead of failing on them.
+    #[arg(long, value_name = "FILE", conflicts_with = "baseline")]
+    pub generate_baseline: Option<PathBuf>,
+    /// Don't use or update the lint result cache, even if `cache_dir` is configured.
+    #[arg(long)]
+    pub no_cache: bool,
+    /// The number of parallel processes to run. Zero and negative numbers
+    /// are subtracted from the number of CPUs, e.g. -1 means all CPUs but one.
+    #[arg(short = 'j', long, allow_negative_numbers = true)]
+    pub processes: Option<i32>,
 }
 
 #[derive(Debug, Parser)]
@@ -42,8 +138,62 @@ pub(crate) struct FixArgs {
     /// Skip the confirmation prompt and go straight to applying fixes.
     #[arg(long)]
     pub force: bool,
+    /// Don't write any files, print a unified diff of the fixes to each file instead and exit
+    /// with a non-zero code if there are any.
+    #[arg(long, conflicts_with = "check")]
+    pub diff: bool,
+    /// Don't write any files, exit with a non-zero code if any file would be fixed.
+    #[arg(long)]
+    pub check: bool,
     #[arg(default_value_t, short, long)]
     pub format: Format,
+    /// The number of parallel processes to run. Zero and negative numbers
+    /// are subtracted from the number of CPUs, e.g. -1 means all CPUs but one.
+    #[arg(short = 'j', long, allow_negative_numbers = true)]
+    pub processes: Option<i32>,
+}
+
+#[derive(Debug, Parser)]
+pub(crate) struct ParseArgs {
+    /// Files to parse. Use `-` to read from stdin.
+    pub paths: Vec<PathBuf>,
+    #[arg(default_value_t, short, long)]
+    pub format: ParseFormat,
+    /// Only include code segments, leaving out whitespace, comments and indents.
+    #[arg(long)]
+    pub code_only: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, ValueEnum, Display)]
+#[strum(serialize_all = "kebab-case")]
+pub(crate) enum ParseFormat {
+    #[default]
+    Human,
+    Yaml,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum, Display)]
+#[strum(serialize_all = "kebab-case")]
+pub(crate) enum IgnoreCategory {
+    Lexing,
+    Linting,
+    Parsing,
+    Templating,
+}
+
+#[derive(Debug, Parser)]
+pub(crate) struct ListArgs {
+    #[arg(default_value_t, short, long)]
+    pub format: ListFormat,
+}
+
+#[derive(Debug, Clone, Copy, Default, ValueEnum, Display)]
+#[strum(serialize_all = "kebab-case")]
+pub(crate) enum ListFormat {
+    #[default]
+    Human,
+    Json,
 }
 
 #[derive(Debug, Clone, Copy, ValueEnum, Display)]
@@ -52,6 +202,11 @@ pub(crate) enum Format {
     Human,
     GithubAnnotationNative,
     Json,
+    JsonV2,
+    Sarif,
+    Junit,
+    Checkstyle,
+    Gitlab,
 }
 
 impl Default for Format {
@@ -0,0 +1,69 @@
+use std::str::FromStr;
+
+use serde::Serialize;
+use sqruff_lib_core::dialects::init::{dialect_readout, DialectKind};
+use sqruff_lib_dialects::is_dialect_enabled;
+
+use crate::commands::{ListArgs, ListFormat};
+
+/// A dialect as listed by `sqruff dialects`.
+#[derive(Debug, Serialize)]
+struct DialectInfo {
+    name: String,
+    /// The cargo feature which compiles the dialect in, if it has one.
+    feature: Option<String>,
+    enabled: bool,
+}
+
+fn catalogue() -> Vec<DialectInfo> {
+    dialect_readout()
+        .into_iter()
+        .map(|name| {
+            let kind = DialectKind::from_str(&name).unwrap();
+            DialectInfo {
+                feature: (kind != DialectKind::Ansi).then(|| name.clone()),
+                enabled: is_dialect_enabled(kind),
+                name,
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn run_dialects(args: ListArgs) -> i32 {
+    let catalogue = catalogue();
+
+    match args.format {
+        ListFormat::Human => {
+            for dialect in &catalogue {
+                if dialect.enabled {
+                    println!("{}", dialect.name);
+                } else {
+                    println!("{} (not compiled in)", dialect.name);
+                }
+            }
+        }
+        ListFormat::Json => println!("{}", serde_json::to_string(&catalogue).unwrap()),
+    }
+
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::catalogue;
+
+    #[test]
+    fn test_dialects_catalogue() {
+        let catalogue = catalogue();
+
+        assert_eq!(catalogue[0].name, "ansi");
+        assert_eq!(catalogue[0].feature, None);
+        assert!(catalogue[0].enabled);
+
+        let postgres = catalogue
+            .iter()
+            .find(|dialect| dialect.name == "postgres")
+            .unwrap();
+        assert_eq!(postgres.feature.as_deref(), Some("postgres"));
+    }
+}
@@ -1,23 +1,61 @@
 use crate::check_user_input;
 use crate::commands::FixArgs;
-use crate::commands::Format;
 use crate::linter;
-use sqruff_lib::core::config::FluffConfig;
+use similar::TextDiff;
+use sqruff_lib::core::config::{FluffConfig, Value};
+use sqruff_lib::core::linter::core::Linter;
 use std::path::Path;
 
 pub(crate) fn run_fix(
     args: FixArgs,
-    config: FluffConfig,
+    mut config: FluffConfig,
     ignorer: impl Fn(&Path) -> bool + Send + Sync,
 ) -> i32 {
     let FixArgs {
         paths,
         force,
+        diff,
+        check,
         format,
+        processes,
     } = args;
-    let mut linter = linter(config, format);
+    if let Some(processes) = processes {
+        config.set_value("processes", "core", Value::Int(processes));
+    }
+    // The diff is the whole output, so it can be piped to other tools.
+    let mut linter = if diff {
+        Linter::new(config, None, None)
+    } else {
+        linter(config, format)
+    };
     let result = linter.lint_paths(paths, true, &ignorer);
 
+    if diff || check {
+        let mut changed = false;
+        for linted_dir in result.paths {
+            for file in linted_dir.files {
+                let path = file.path.clone();
+                let source = file.templated_file.source_str.clone();
+                let fixed = file.fix_string();
+                if fixed == source {
+                    continue;
+                }
+
+                changed = true;
+                if diff {
+                    print!("{}", unified_diff(&path, &source, &fixed));
+                } else {
+                    eprintln!("Would fix {path}");
+                }
+            }
+        }
+
+        if !diff {
+            linter.formatter_mut().unwrap().completion_message();
+        }
+        return if changed { 1 } else { 0 };
+    }
+
     if result
         .paths
         .iter()
@@ -58,15 +96,31 @@ pub(crate) fn run_fix(
     }
 }
 
-pub(crate) fn run_fix_stdin(config: FluffConfig, format: Format) -> i32 {
+pub(crate) fn run_fix_stdin(args: FixArgs, config: FluffConfig) -> i32 {
     let read_in = crate::stdin::read_std_in().unwrap();
 
-    let linter = linter(config, format);
+    let linter = if args.diff {
+        Linter::new(config, None, None)
+    } else {
+        linter(config, args.format)
+    };
     let result = linter.lint_string(&read_in, None, true);
 
-    // print fixed to std out
     let violations = result.get_violations(Some(false));
-    println!("{}", result.fix_string());
+    let fixed = result.fix_string();
+
+    if args.diff || args.check {
+        // The fixed SQL has normalised newlines, so compare it with the input in the same form.
+        let source = Linter::normalise_newlines(&read_in);
+        let changed = fixed != source;
+        if args.diff && changed {
+            print!("{}", unified_diff("<stdin>", &source, &fixed));
+        }
+        return if changed { 1 } else { 0 };
+    }
+
+    // print fixed to std out
+    println!("{}", fixed);
 
     // if all fixable violations are fixable, return 0 else return 1
     if violations.is_empty() {
@@ -75,3 +129,11 @@ pub(crate) fn run_fix_stdin(config: FluffConfig, format: Format) -> i32 {
         1
     }
 }
+
+/// A unified diff between the source of a file and its fixed version.
+fn unified_diff(path: &str, source: &str, fixed: &str) -> String {
+    TextDiff::from_lines(source, fixed)
+        .unified_diff()
+        .header(path, path)
+        .to_string()
+}
@@ -1,16 +1,75 @@
 use crate::commands::{Format, LintArgs};
 use crate::linter;
-use sqruff_lib::core::config::FluffConfig;
+use sqruff_lib::core::config::{FluffConfig, Value};
+use sqruff_lib::core::linter::baseline::Baseline;
+use sqruff_lib::core::linter::changed_lines::ChangedLines;
+use sqruff_lib::core::linter::core::Linter;
 use std::path::Path;
 
 pub(crate) fn run_lint(
     args: LintArgs,
-    config: FluffConfig,
+    mut config: FluffConfig,
     ignorer: impl Fn(&Path) -> bool + Send + Sync,
 ) -> i32 {
-    let LintArgs { paths, format } = args;
-    let mut linter = linter(config, format);
-    linter.lint_paths(paths, false, &ignorer);
+    let LintArgs {
+        paths,
+        format,
+        processes,
+        diff_base,
+        baseline,
+        generate_baseline,
+        no_cache,
+    } = args;
+    if let Some(processes) = processes {
+        config.set_value("processes", "core", Value::Int(processes));
+    }
+    if no_cache {
+        config.set_value("cache_dir", "core", Value::None);
+    }
+    // Generating a baseline only reports how many violations it recorded.
+    let mut linter = if generate_baseline.is_some() {
+        Linter::new(config, None, None)
+    } else {
+        linter(config, format)
+    };
+    if let Some(diff_base) = diff_base {
+        match ChangedLines::from_git(&diff_base) {
+            Ok(changed_lines) => linter.set_changed_lines(changed_lines),
+            Err(error) => {
+                eprintln!("{}", error.value);
+                return 1;
+            }
+        }
+    }
+    if let Some(baseline) = baseline {
+        let baseline = std::fs::read_to_string(&baseline)
+            .map_err(|error| format!("Failed to read {}: {error}", baseline.display()))
+            .and_then(|json| Baseline::from_json(&json).map_err(|error| error.value));
+        match baseline {
+            Ok(baseline) => linter.set_baseline(baseline),
+            Err(error) => {
+                eprintln!("{error}");
+                return 1;
+            }
+        }
+    }
+
+    let result = linter.lint_paths(paths, false, &ignorer);
+
+    if let Some(path) = generate_baseline {
+        let baseline =
+            Baseline::from_linted_files(result.paths.iter().flat_map(|dir| dir.files.iter()));
+        if let Err(error) = std::fs::write(&path, baseline.to_json()) {
+            eprintln!("Failed to write {}: {error}", path.display());
+            return 1;
+        }
+        eprintln!(
+            "Recorded {} violation(s) in {}",
+            baseline.len(),
+            path.display()
+        );
+        return 0;
+    }
 
     linter.formatter().unwrap().completion_message();
     if linter.formatter().unwrap().has_fail() {
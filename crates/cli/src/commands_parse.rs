@@ -0,0 +1,215 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::Serialize;
+use sqruff_lib::core::config::FluffConfig;
+use sqruff_lib::core::linter::core::Linter;
+use sqruff_lib_core::dialects::syntax::SyntaxKind;
+use sqruff_lib_core::parser::segments::base::{ErasedSegment, Tables};
+
+use crate::commands::{ParseArgs, ParseFormat};
+
+/// A segment of the parse tree, as printed by `sqruff parse`.
+#[derive(Debug, Serialize)]
+struct Node {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    /// The raw text of leaf segments.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    raw: Option<String>,
+    line_no: usize,
+    line_pos: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    children: Vec<Node>,
+}
+
+impl Node {
+    fn new(segment: &ErasedSegment, code_only: bool) -> Self {
+        let (line_no, line_pos) = segment
+            .get_position_marker()
+            .map_or((0, 0), |marker| marker.source_position());
+
+        let children = segment
+            .segments()
+            .iter()
+            .filter(|child| !code_only || (child.is_code() && !child.is_meta()))
+            .map(|child| Node::new(child, code_only))
+            .collect();
+
+        Node {
+            kind: segment.get_type().as_str(),
+            raw: segment
+                .segments()
+                .is_empty()
+                .then(|| segment.raw().to_string()),
+            line_no,
+            line_pos,
+            children,
+        }
+    }
+
+    fn unparsable_count(&self) -> usize {
+        let own = usize::from(self.kind == SyntaxKind::Unparsable.as_str());
+        own + self
+            .children
+            .iter()
+            .map(Node::unparsable_count)
+            .sum::<usize>()
+    }
+
+    /// Render the tree as indented text, one segment per line.
+    fn write_human(&self, depth: usize, out: &mut String) {
+        let position = format!("[L:{:3}, P:{:3}]", self.line_no, self.line_pos);
+        let label = format!("{}{}:", "    ".repeat(depth), self.kind);
+        match &self.raw {
+            Some(raw) => out.push_str(&format!("{position}      |{label:<48}{raw:?}\n")),
+            None => out.push_str(&format!("{position}      |{label}\n")),
+        }
+
+        for child in &self.children {
+            child.write_human(depth + 1, out);
+        }
+    }
+}
+
+pub(crate) fn run_parse(args: ParseArgs, config: FluffConfig) -> i32 {
+    let ParseArgs {
+        paths,
+        format,
+        code_only,
+    } = args;
+
+    let from_stdin = paths.len() == 1 && paths[0] == PathBuf::from("-");
+    let sources = if from_stdin {
+        match crate::stdin::read_std_in() {
+            Ok(sql) => vec![("stdin".to_string(), sql)],
+            Err(error) => {
+                eprintln!("{error}");
+                return 1;
+            }
+        }
+    } else {
+        let mut sources = Vec::with_capacity(paths.len());
+        for path in paths {
+            match std::fs::read_to_string(&path) {
+                Ok(sql) => sources.push((path.to_string_lossy().into_owned(), sql)),
+                Err(error) => {
+                    eprintln!("Failed to read {}: {error}", path.display());
+                    return 1;
+                }
+            }
+        }
+        sources
+    };
+
+    let linter = Linter::new(config, None, None);
+    let tables = Tables::default();
+    let mut status_code = 0;
+    let mut trees = BTreeMap::new();
+
+    for (filename, sql) in sources {
+        // SQL from stdin has no path of its own, so it uses the config of the
+        // working directory.
+        let config = if from_stdin {
+            Ok(linter.config().clone())
+        } else {
+            linter.config().make_child_from_path(&filename)
+        };
+        let rendered =
+            config.and_then(|config| linter.render_string(&sql, filename.clone(), &config));
+        let rendered = match rendered {
+            Ok(rendered) => rendered,
+            Err(error) => {
+                eprintln!("{}", error.value);
+                status_code = 1;
+                continue;
+            }
+        };
+        let parsed = linter.parse_rendered(&tables, rendered);
+
+        for violation in &parsed.violations {
+            eprintln!(
+                "{filename} L:{:4} | P:{:4} | {} | {}",
+                violation.line_no,
+                violation.line_pos,
+                violation.rule_code(),
+                violation.desc()
+            );
+        }
+
+        let tree = parsed.tree.map(|tree| Node::new(&tree, code_only));
+        let unparsable = tree.as_ref().map_or(0, Node::unparsable_count);
+        if unparsable > 0 {
+            eprintln!("{filename} contains {unparsable} unparsable section(s).");
+        }
+        if unparsable > 0 || !parsed.violations.is_empty() {
+            status_code = 1;
+        }
+
+        trees.insert(filename, tree);
+    }
+
+    match format {
+        ParseFormat::Human => {
+            let multiple = trees.len() > 1;
+            for (filename, tree) in &trees {
+                if multiple {
+                    println!("== {filename} ==");
+                }
+                let mut out = String::new();
+                if let Some(tree) = tree {
+                    tree.write_human(0, &mut out);
+                }
+                print!("{out}");
+            }
+        }
+        ParseFormat::Yaml => print!("{}", serde_yaml::to_string(&trees).unwrap()),
+        ParseFormat::Json => println!("{}", serde_json::to_string(&trees).unwrap()),
+    }
+
+    status_code
+}
+
+#[cfg(test)]
+mod tests {
+    use sqruff_lib::core::config::FluffConfig;
+    use sqruff_lib::core::linter::core::Linter;
+    use sqruff_lib_core::parser::segments::base::Tables;
+
+    use super::Node;
+
+    fn parse(sql: &str, code_only: bool) -> Node {
+        let linter = Linter::new(FluffConfig::new(<_>::default(), None, None), None, None);
+        let tables = Tables::default();
+        let parsed = linter.parse_string(&tables, sql, None).unwrap();
+        Node::new(&parsed.tree.unwrap(), code_only)
+    }
+
+    fn leaves(node: &Node) -> Vec<(&'static str, &str, usize, usize)> {
+        match &node.raw {
+            Some(raw) => vec![(node.kind, raw.as_str(), node.line_no, node.line_pos)],
+            None => node.children.iter().flat_map(leaves).collect(),
+        }
+    }
+
+    #[test]
+    fn test_parse_tree() {
+        let tree = parse("select a\nfrom b\n", true);
+        assert_eq!(tree.kind, "file");
+        assert_eq!(tree.unparsable_count(), 0);
+        assert_eq!(
+            leaves(&tree),
+            [
+                ("keyword", "select", 1, 1),
+                ("naked_identifier", "a", 1, 8),
+                ("keyword", "from", 2, 1),
+                ("naked_identifier", "b", 2, 6),
+            ]
+        );
+
+        let tree = parse("select a\nfrom b\n", false);
+        assert!(leaves(&tree)
+            .iter()
+            .any(|(kind, _, _, _)| *kind == "whitespace"));
+    }
+}
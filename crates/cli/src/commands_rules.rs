@@ -0,0 +1,100 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use sqruff_lib::core::config::{FluffConfig, Value};
+use sqruff_lib::rules::rules;
+
+use crate::commands::{ListArgs, ListFormat};
+
+/// A rule as listed by `sqruff rules`.
+#[derive(Debug, Serialize)]
+struct RuleInfo {
+    code: &'static str,
+    name: &'static str,
+    description: &'static str,
+    groups: Vec<&'static str>,
+    fixable: bool,
+    /// The options of the rule with their default values.
+    config: BTreeMap<String, Value>,
+    /// The dialects the rule is skipped for.
+    dialect_skip: Vec<&'static str>,
+}
+
+fn catalogue() -> Vec<RuleInfo> {
+    let config = FluffConfig::default();
+    let rules_config = config.get_section("rules");
+
+    rules()
+        .into_iter()
+        .map(|rule| RuleInfo {
+            code: rule.code(),
+            name: rule.name(),
+            description: rule.description(),
+            groups: rule.groups().iter().map(AsRef::as_ref).collect(),
+            fixable: rule.is_fix_compatible(),
+            config: rules_config
+                .get(rule.config_ref())
+                .and_then(Value::as_map)
+                .map(|options| {
+                    options
+                        .iter()
+                        .map(|(key, value)| (key.clone(), value.clone()))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            dialect_skip: rule
+                .dialect_skip()
+                .iter()
+                .map(|dialect| dialect.as_ref())
+                .collect(),
+        })
+        .collect()
+}
+
+pub(crate) fn run_rules(args: ListArgs) -> i32 {
+    let catalogue = catalogue();
+
+    match args.format {
+        ListFormat::Human => {
+            for rule in &catalogue {
+                let fixable = if rule.fixable { "fixable" } else { "" };
+                println!(
+                    "{:<5} {:<40} {:<8} {}",
+                    rule.code,
+                    rule.name,
+                    fixable,
+                    rule.groups.join(", ")
+                );
+                println!("      {}", rule.description);
+                if !rule.config.is_empty() {
+                    let keys = rule.config.keys().map(String::as_str).collect::<Vec<_>>();
+                    println!("      config: {}", keys.join(", "));
+                }
+                if !rule.dialect_skip.is_empty() {
+                    println!("      skipped for: {}", rule.dialect_skip.join(", "));
+                }
+            }
+        }
+        ListFormat::Json => println!("{}", serde_json::to_string(&catalogue).unwrap()),
+    }
+
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::catalogue;
+
+    #[test]
+    fn test_rules_catalogue() {
+        let catalogue = catalogue();
+
+        let lt01 = catalogue.iter().find(|rule| rule.code == "LT01").unwrap();
+        assert_eq!(lt01.name, "layout.spacing");
+        assert!(lt01.fixable);
+        assert_eq!(lt01.groups[0], "all");
+
+        let cp01 = catalogue.iter().find(|rule| rule.code == "CP01").unwrap();
+        assert!(cp01.config.contains_key("capitalisation_policy"));
+    }
+}
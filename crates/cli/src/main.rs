@@ -1,12 +1,15 @@
 use clap::Parser as _;
 use commands::Format;
 use sqruff_lib::cli::formatters::Formatter;
+use sqruff_lib::cli::gitlab::GitlabFormatter;
 use sqruff_lib::cli::json::JsonFormatter;
+use sqruff_lib::cli::sarif::SarifFormatter;
+use sqruff_lib::cli::xml::{CheckstyleFormatter, JUnitFormatter};
 use sqruff_lib::cli::{
     formatters::OutputStreamFormatter,
     github_annotation_native_formatter::GithubAnnotationNativeFormatter,
 };
-use sqruff_lib::core::config::FluffConfig;
+use sqruff_lib::core::config::{ConfigLoader, FluffConfig};
 use sqruff_lib::core::linter::core::Linter;
 use std::path::Path;
 use std::sync::Arc;
@@ -17,8 +20,11 @@ use crate::commands::{Cli, Commands};
 use crate::docs::codegen_docs;
 
 mod commands;
+mod commands_dialects;
 mod commands_fix;
 mod commands_lint;
+mod commands_parse;
+mod commands_rules;
 #[cfg(feature = "codegen-docs")]
 mod docs;
 mod github_action;
@@ -48,7 +54,15 @@ fn main() {
 
     let cli = Cli::parse();
 
-    let config: FluffConfig = if let Some(config) = cli.config.as_ref() {
+    let overrides = match cli.config_overrides() {
+        Ok(overrides) => overrides,
+        Err(error) => {
+            eprintln!("{error}");
+            std::process::exit(1);
+        }
+    };
+
+    let config = if let Some(config) = cli.config.as_ref() {
         if !Path::new(config).is_file() {
             eprintln!(
                 "The specified config file '{}' does not exist.",
@@ -58,9 +72,20 @@ fn main() {
             std::process::exit(1);
         };
         let read_file = std::fs::read_to_string(config).unwrap();
-        FluffConfig::from_source(&read_file)
+        ConfigLoader.from_source(&read_file).map(|configs| {
+            FluffConfig::new(configs, None, None)
+                .explicit()
+                .with_overrides(overrides)
+        })
     } else {
-        FluffConfig::from_root(None, false, None).unwrap()
+        FluffConfig::from_root(None, false, Some(overrides))
+    };
+    let config = match config {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("{}", error.value);
+            std::process::exit(1);
+        }
     };
 
     let current_path = std::env::current_dir().unwrap();
@@ -78,6 +103,14 @@ fn main() {
                 1
             }
             Ok(false) => commands_lint::run_lint(args, config, ignorer),
+            Ok(true)
+                if args.diff_base.is_some()
+                    || args.baseline.is_some()
+                    || args.generate_baseline.is_some() =>
+            {
+                eprintln!("--diff-base and baselines can't be used when reading from stdin");
+                1
+            }
             Ok(true) => commands_lint::run_lint_stdin(config, args.format),
         },
         Commands::Fix(args) => match is_std_in_flag_input(&args.paths) {
@@ -86,8 +119,17 @@ fn main() {
                 1
             }
             Ok(false) => commands_fix::run_fix(args, config, ignorer),
-            Ok(true) => commands_fix::run_fix_stdin(config, args.format),
+            Ok(true) => commands_fix::run_fix_stdin(args, config),
+        },
+        Commands::Parse(args) => match is_std_in_flag_input(&args.paths) {
+            Err(e) => {
+                eprintln!("{e}");
+                1
+            }
+            Ok(_) => commands_parse::run_parse(args, config),
         },
+        Commands::Rules(args) => commands_rules::run_rules(args),
+        Commands::Dialects(args) => commands_dialects::run_dialects(args),
         Commands::Lsp => {
             sqruff_lsp::run();
             0
@@ -116,6 +158,26 @@ pub(crate) fn linter(config: FluffConfig, format: Format) -> Linter {
             let formatter = JsonFormatter::default();
             Arc::new(formatter)
         }
+        Format::JsonV2 => {
+            let formatter = JsonFormatter::detailed();
+            Arc::new(formatter)
+        }
+        Format::Sarif => {
+            let formatter = SarifFormatter::default();
+            Arc::new(formatter)
+        }
+        Format::Junit => {
+            let formatter = JUnitFormatter::default();
+            Arc::new(formatter)
+        }
+        Format::Checkstyle => {
+            let formatter = CheckstyleFormatter::default();
+            Arc::new(formatter)
+        }
+        Format::Gitlab => {
+            let formatter = GitlabFormatter::default();
+            Arc::new(formatter)
+        }
     };
 
     Linter::new(config, Some(formatter), None)
@@ -0,0 +1,88 @@
+use std::path::{Path, PathBuf};
+
+use assert_cmd::Command;
+use expect_test::expect_file;
+
+fn main() {
+    fix_diff();
+    fix_check_stdin_crlf();
+}
+
+fn fix_diff() {
+    let profile = if cfg!(debug_assertions) {
+        "debug"
+    } else {
+        "release"
+    };
+
+    let cargo_folder = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let file_dir = cargo_folder.join("tests/fix_diff");
+    let sql_path = file_dir.join("example.sql");
+    let source = std::fs::read_to_string(&sql_path).unwrap();
+
+    // Construct the path to the sqruff binary
+    let mut sqruff_path = PathBuf::from(cargo_folder);
+    sqruff_path.push(format!("../../target/{}/sqruff", profile));
+
+    // Set up the command with arguments
+    let mut cmd = Command::new(sqruff_path);
+    cmd.arg("fix")
+        .arg("--diff")
+        .arg("--config")
+        .arg(file_dir.join("lt01.cfg"))
+        .arg(&sql_path);
+    cmd.current_dir(cargo_folder);
+
+    // Set the HOME environment variable to the fake home directory
+    cmd.env("HOME", PathBuf::from(env!("CARGO_MANIFEST_DIR")));
+
+    // Run the command and capture the output
+    let assert = cmd.assert();
+    let output = assert.get_output();
+    let stdout_str = std::str::from_utf8(&output.stdout).unwrap();
+    // Nothing but the diff is printed.
+    assert!(output.stderr.is_empty());
+    let exit_code_str = output.status.code().unwrap().to_string();
+
+    let test_dir_str = file_dir.to_string_lossy().to_string();
+    let stdout_normalized: String = stdout_str.replace(&test_dir_str, "tests/fix_diff");
+
+    expect_file![file_dir.join("example.stdout")].assert_eq(&stdout_normalized);
+    expect_file![file_dir.join("example.exitcode")].assert_eq(&exit_code_str);
+
+    // The file itself is left untouched.
+    assert_eq!(std::fs::read_to_string(&sql_path).unwrap(), source);
+}
+
+/// Newlines are normalised when fixing, so CRLF input without violations isn't
+/// reported as changed.
+fn fix_check_stdin_crlf() {
+    let profile = if cfg!(debug_assertions) {
+        "debug"
+    } else {
+        "release"
+    };
+
+    let cargo_folder = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let file_dir = cargo_folder.join("tests/fix_diff");
+
+    let mut sqruff_path = PathBuf::from(cargo_folder);
+    sqruff_path.push(format!("../../target/{}/sqruff", profile));
+
+    for flag in ["--check", "--diff"] {
+        let mut cmd = Command::new(&sqruff_path);
+        cmd.arg("fix")
+            .arg(flag)
+            .arg("--config")
+            .arg(file_dir.join("lt01.cfg"))
+            .arg("-")
+            .write_stdin("SELECT 1\r\nFROM foo\r\n");
+        cmd.current_dir(cargo_folder);
+        cmd.env("HOME", PathBuf::from(env!("CARGO_MANIFEST_DIR")));
+
+        let assert = cmd.assert();
+        let output = assert.get_output();
+        assert!(output.stdout.is_empty());
+        assert_eq!(output.status.code(), Some(0));
+    }
+}
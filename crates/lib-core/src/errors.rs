@@ -27,6 +27,15 @@ pub struct SQLBaseError {
     pub rule: Option<ErrorStructRule>,
     pub source_slice: Range<usize>,
     pub fixable: bool,
+    pub fixes: Vec<SourceEdit>,
+}
+
+/// An edit to the source file which fixes an error, replacing the text in
+/// `source_slice` with `edit`.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct SourceEdit {
+    pub source_slice: Range<usize>,
+    pub edit: String,
 }
 
 #[derive(Debug, PartialEq, Clone, Default)]
@@ -230,8 +239,7 @@ impl SQLLexError {
 
 #[derive(Debug)]
 pub struct SQLFluffSkipFile {
-    #[allow(dead_code)]
-    value: String,
+    pub value: String,
 }
 
 impl SQLFluffSkipFile {
@@ -3,6 +3,7 @@ use std::ops::Range;
 use ahash::AHashSet;
 
 use crate::edit_type::EditType;
+use crate::errors::SourceEdit;
 use crate::parser::segments::base::ErasedSegment;
 use crate::templaters::base::{RawFileSlice, TemplatedFile};
 
@@ -102,6 +103,42 @@ impl LintFix {
         }
     }
 
+    /// Return the edits this fix makes to the source file.
+    pub fn source_edits(&self) -> Vec<SourceEdit> {
+        let Some(marker) = self.anchor.get_position_marker() else {
+            return Vec::new();
+        };
+
+        if self.is_just_source_edit() {
+            return self
+                .edit
+                .as_deref()
+                .unwrap_or_default()
+                .iter()
+                .flat_map(|segment| segment.get_source_fixes())
+                .map(|fix| SourceEdit {
+                    source_slice: fix.source_slice,
+                    edit: fix.edit.to_string(),
+                })
+                .collect();
+        }
+
+        let source_slice = match self.edit_type {
+            EditType::CreateBefore => marker.source_slice.start..marker.source_slice.start,
+            EditType::CreateAfter => marker.source_slice.end..marker.source_slice.end,
+            EditType::Replace | EditType::Delete => marker.source_slice.clone(),
+        };
+        let edit = self
+            .edit
+            .as_deref()
+            .unwrap_or_default()
+            .iter()
+            .map(|segment| segment.raw().as_str())
+            .collect();
+
+        vec![SourceEdit { source_slice, edit }]
+    }
+
     fn fix_slices(
         &self,
         templated_file: &TemplatedFile,
@@ -642,7 +642,7 @@ fn iter_segments(
 
     // Now work out source slices, and add in template placeholders.
     for element in lexed_elements.into_iter() {
-        let consumed_element_length = 0;
+        let mut consumed_element_length = 0;
         let mut stashed_source_idx = None;
 
         for (idx, tfs) in templated_file_slices
@@ -675,7 +675,8 @@ fn iter_segments(
                     result.push(element.to_segment(
                         PositionMarker::new(
                             slice_start..element.template_slice.end + tfs_offset,
-                            element.template_slice.clone(),
+                            element.template_slice.start + consumed_element_length
+                                ..element.template_slice.end,
                             templated_file.clone(),
                             None,
                             None,
@@ -689,7 +690,7 @@ fn iter_segments(
                     }
                     // In any case, we're done with this element. Move on
                     break;
-                } else if element.template_slice.start == tfs.templated_slice.end {
+                } else if element.template_slice.start >= tfs.templated_slice.end {
                     // Did we forget to move on from the last tfs and there's
                     // overlap?
                     // NOTE: If the rest of the logic works, this should never
@@ -720,20 +721,22 @@ fn iter_segments(
                             PositionMarker::new(
                                 element.template_slice.start + consumed_element_length + tfs_offset
                                     ..tfs.templated_slice.end + tfs_offset,
-                                element.template_slice.clone(),
+                                element.template_slice.start + consumed_element_length
+                                    ..tfs.templated_slice.end,
                                 templated_file.clone(),
                                 None,
                                 None,
                             ),
                             offset_slice(consumed_element_length, incremental_length).into(),
                         ));
+                        consumed_element_length += incremental_length;
                     } else {
                         // We can't split it. We're going to end up yielding a segment
                         // which spans multiple slices. Stash the type, and if we haven't
                         // set the start yet, stash it too.
                         // lexer_logger.debug("     Spilling over literal slice.")
                         if stashed_source_idx.is_none() {
-                            stashed_source_idx = (element.template_slice.start + idx).into();
+                            stashed_source_idx = (element.template_slice.start + tfs_offset).into();
                             // lexer_logger.debug(
                             //     "     Stashing a source start. %s", stashed_source_idx
                             // )
@@ -745,6 +748,11 @@ fn iter_segments(
                 // Found a templated slice. Does it have length in the templated file?
                 // If it doesn't, then we'll pick it up next.
                 if !is_zero_slice(&tfs.templated_slice) {
+                    // Skip slices which this element starts after.
+                    if element.template_slice.start >= tfs.templated_slice.end {
+                        continue;
+                    }
+
                     // If it's a block_start. Append to the block stack.
                     // NOTE: This is rare, but call blocks do occasionally
                     // have length (and so don't get picked up by
@@ -770,7 +778,8 @@ fn iter_segments(
                         result.push(element.to_segment(
                             PositionMarker::new(
                                 slice_start..tfs.source_slice.end,
-                                element.template_slice.clone(),
+                                element.template_slice.start + consumed_element_length
+                                    ..element.template_slice.end,
                                 templated_file.clone(),
                                 None,
                                 None,
@@ -785,7 +794,13 @@ fn iter_segments(
                         // Carry on to the next lexed element
                         break;
                     } else {
-                        unimplemented!()
+                        // The element spans beyond the end of this templated slice, so
+                        // stash the start and pick up the rest from the next slice.
+                        if stashed_source_idx.is_none() {
+                            stashed_source_idx =
+                                (tfs.source_slice.start + consumed_element_length).into();
+                        }
+                        continue;
                     }
                 }
             }
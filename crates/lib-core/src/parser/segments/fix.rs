@@ -36,8 +36,8 @@ pub struct FixPatch {
     // generated. It has no significance for processing. Brought over from sqlfluff
     // patch_category: FixPatchCategory,
     pub source_slice: Range<usize>,
-    templated_str: String,
-    source_str: String,
+    pub templated_str: String,
+    pub source_str: String,
 }
 
 impl FixPatch {
@@ -48,6 +48,25 @@ pub mod trino;
 #[cfg(feature = "trino")]
 mod trino_keywords;
 
+/// Whether the cargo feature of a dialect was enabled for this build, so that
+/// [`kind_to_dialect`] returns it.
+pub fn is_dialect_enabled(kind: DialectKind) -> bool {
+    match kind {
+        DialectKind::Ansi => true,
+        DialectKind::Athena => cfg!(feature = "athena"),
+        DialectKind::Bigquery => cfg!(feature = "bigquery"),
+        DialectKind::Clickhouse => cfg!(feature = "clickhouse"),
+        DialectKind::Databricks => cfg!(feature = "databricks"),
+        DialectKind::Duckdb => cfg!(feature = "duckdb"),
+        DialectKind::Postgres => cfg!(feature = "postgres"),
+        DialectKind::Redshift => cfg!(feature = "redshift"),
+        DialectKind::Snowflake => cfg!(feature = "snowflake"),
+        DialectKind::Sparksql => cfg!(feature = "sparksql"),
+        DialectKind::Sqlite => cfg!(feature = "sqlite"),
+        DialectKind::Trino => cfg!(feature = "trino"),
+    }
+}
+
 pub fn kind_to_dialect(kind: &DialectKind) -> Option<Dialect> {
     #[allow(unreachable_patterns)]
     Some(match kind {
@@ -1,4 +1,7 @@
 pub mod formatters;
 pub mod github_annotation_native_formatter;
+pub mod gitlab;
 pub mod json;
 pub mod json_types;
+pub mod sarif;
+pub mod xml;
@@ -4,7 +4,7 @@ use std::sync::atomic::{AtomicBool, AtomicUsize};
 
 use anstyle::{AnsiColor, Effects, Style};
 use itertools::enumerate;
-use sqruff_lib_core::errors::SQLBaseError;
+use sqruff_lib_core::errors::{SQLBaseError, SQLFluffSkipFile};
 
 use crate::core::config::FluffConfig;
 use crate::core::linter::linted_file::LintedFile;
@@ -52,6 +52,14 @@ pub trait Formatter: Send + Sync {
 
     fn dispatch_file_violations(&self, linted_file: &LintedFile, only_fixable: bool);
 
+    /// Reports a file which wasn't linted, as a warning by default.
+    fn dispatch_file_skipped(&self, f_name: &str, skip: &SQLFluffSkipFile) {
+        self.dispatch_file_warning(f_name, &skip.value);
+    }
+
+    /// Reports a warning about a file which isn't tied to a violation. Ignored by default.
+    fn dispatch_file_warning(&self, _f_name: &str, _message: &str) {}
+
     fn has_fail(&self) -> bool;
 
     fn completion_message(&self);
@@ -83,6 +91,35 @@ impl Formatter for OutputStreamFormatter {
             .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
     }
 
+    fn dispatch_file_skipped(&self, f_name: &str, skip: &SQLFluffSkipFile) {
+        if self.verbosity < 0 {
+            return;
+        }
+
+        let mut text = self.format_filename(f_name, Status::Skip);
+        text.push('\n');
+        for line in split_string_on_spaces(&skip.value, self.output_line_length) {
+            text.push_str(line);
+            text.push('\n');
+        }
+        self.dispatch(&text);
+    }
+
+    fn dispatch_file_warning(&self, f_name: &str, message: &str) {
+        if self.verbosity < 0 {
+            return;
+        }
+
+        let filename = self.colorize(f_name, LIGHT_GREY);
+        let warning = self.colorize("WARNING", AnsiColor::Yellow.on_default());
+        let mut text = format!("== [{filename}] {warning}\n");
+        for line in split_string_on_spaces(message, self.output_line_length) {
+            text.push_str(line);
+            text.push('\n');
+        }
+        self.dispatch(&text);
+    }
+
     fn has_fail(&self) -> bool {
         self.has_fail.load(std::sync::atomic::Ordering::SeqCst)
     }
@@ -190,6 +227,7 @@ impl OutputStreamFormatter {
 
         let color = match status {
             Status::Pass | Status::Fixed => AnsiColor::Green,
+            Status::Skip => AnsiColor::Yellow,
             Status::Fail | Status::Error => {
                 self.has_fail
                     .store(true, std::sync::atomic::Ordering::SeqCst);
@@ -278,6 +316,12 @@ impl IntoStatus for bool {
     }
 }
 
+impl IntoStatus for Status {
+    fn into_status(self) -> Status {
+        self
+    }
+}
+
 impl IntoStatus for (Status, bool) {
     fn into_status(self) -> Status {
         let (if_ok, is_ok) = self;
@@ -295,6 +339,7 @@ pub enum Status {
     Fixed,
     Fail,
     Error,
+    Skip,
 }
 
 impl Status {
@@ -304,6 +349,7 @@ impl Status {
             Status::Fixed => "FIXED",
             Status::Fail => "FAIL",
             Status::Error => "ERROR",
+            Status::Skip => "SKIP",
         }
     }
 }
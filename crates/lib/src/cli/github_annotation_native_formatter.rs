@@ -24,6 +24,22 @@ impl GithubAnnotationNativeFormatter {
     }
 }
 
+/// Escape a value for use as a property of a workflow command, such as `file`.
+fn escape_property(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for char in value.chars() {
+        match char {
+            '%' => escaped.push_str("%25"),
+            '\r' => escaped.push_str("%0D"),
+            '\n' => escaped.push_str("%0A"),
+            ':' => escaped.push_str("%3A"),
+            ',' => escaped.push_str("%2C"),
+            _ => escaped.push(char),
+        }
+    }
+    escaped
+}
+
 impl Formatter for GithubAnnotationNativeFormatter {
     fn dispatch_template_header(
         &self,
@@ -54,7 +70,7 @@ impl Formatter for GithubAnnotationNativeFormatter {
         for violation in violations {
             let message = format!(
                 "::error title=sqruff,file={},line={},col={}::{}: {}\n",
-                linted_file.path,
+                escape_property(&linted_file.path),
                 violation.line_no,
                 violation.line_pos,
                 violation.rule.as_ref().unwrap().code,
@@ -67,6 +83,14 @@ impl Formatter for GithubAnnotationNativeFormatter {
         }
     }
 
+    fn dispatch_file_warning(&self, f_name: &str, message: &str) {
+        let message = format!(
+            "::warning title=sqruff,file={}::{message}\n",
+            escape_property(f_name)
+        );
+        self.dispatch(&message);
+    }
+
     fn has_fail(&self) -> bool {
         self.has_fail.load(Ordering::SeqCst)
     }
@@ -75,3 +99,16 @@ impl Formatter for GithubAnnotationNativeFormatter {
         // No-op
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::escape_property;
+
+    #[test]
+    fn test_escape_property() {
+        assert_eq!(
+            escape_property("dir,1/a:b%c\r\n.sql"),
+            "dir%2C1/a%3Ab%25c%0D%0A.sql"
+        );
+    }
+}
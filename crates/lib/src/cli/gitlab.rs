@@ -0,0 +1,194 @@
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+
+use ahash::AHashSet;
+use itertools::Itertools;
+use serde::Serialize;
+use sqruff_lib_core::errors::SQLBaseError;
+
+use super::formatters::Formatter;
+use crate::core::config::FluffConfig;
+use crate::core::linter::common::{portable_path, StableHasher};
+use crate::core::linter::linted_file::LintedFile;
+
+/// Prints a [GitLab Code Quality](https://docs.gitlab.com/ee/ci/testing/code_quality.html#code-quality-report-format)
+/// report once linting has finished.
+#[derive(Default)]
+pub struct GitlabFormatter {
+    issues: Mutex<BTreeMap<String, Vec<Issue>>>,
+}
+
+impl GitlabFormatter {
+    /// Build the issues of a file.
+    ///
+    /// GitLab expects paths relative to the repository, so the path is made
+    /// relative to the working directory. Fingerprints hash that path, the rule
+    /// code and the offending source with normalised whitespace, so they don't
+    /// change when unrelated lines move, between checkouts or when sqruff is
+    /// upgraded.
+    /// Identical violations within a file are told apart by their order.
+    fn issues<'a>(
+        path: &str,
+        source: &str,
+        violations: impl IntoIterator<Item = &'a SQLBaseError>,
+    ) -> Vec<Issue> {
+        let path = portable_path(path);
+        let mut fingerprints = AHashSet::new();
+
+        violations
+            .into_iter()
+            .map(|violation| {
+                let offending = source
+                    .get(violation.source_slice.clone())
+                    .unwrap_or_default()
+                    .split_whitespace()
+                    .join(" ");
+                let offending = if offending.is_empty() {
+                    violation.desc()
+                } else {
+                    offending.as_str()
+                };
+
+                let mut hasher = StableHasher::default();
+                path.hash(&mut hasher);
+                violation.rule_code().hash(&mut hasher);
+                offending.hash(&mut hasher);
+                let mut fingerprint = hasher.finish();
+                while !fingerprints.insert(fingerprint) {
+                    hasher.write(&fingerprint.to_le_bytes());
+                    fingerprint = hasher.finish();
+                }
+
+                Issue {
+                    description: violation.desc().to_owned(),
+                    check_name: violation.rule_code(),
+                    fingerprint: format!("{fingerprint:016x}"),
+                    severity: if violation.warning { "minor" } else { "major" },
+                    location: Location {
+                        path: path.clone(),
+                        lines: Lines {
+                            begin: violation.line_no.max(1),
+                        },
+                    },
+                }
+            })
+            .collect()
+    }
+}
+
+impl Formatter for GitlabFormatter {
+    fn dispatch_file_violations(&self, linted_file: &LintedFile, only_fixable: bool) {
+        let violations = linted_file.get_violations(only_fixable.then_some(true));
+        let issues = Self::issues(
+            &linted_file.path,
+            &linted_file.templated_file.source_str,
+            &violations,
+        );
+
+        let mut lock = self.issues.lock().unwrap();
+        lock.entry(linted_file.path.clone())
+            .or_default()
+            .extend(issues);
+    }
+
+    fn dispatch_file_warning(&self, f_name: &str, message: &str) {
+        let warning = SQLBaseError {
+            warning: true,
+            description: message.to_owned(),
+            ..SQLBaseError::default()
+        };
+        let issues = Self::issues(f_name, "", [&warning]);
+
+        let mut lock = self.issues.lock().unwrap();
+        lock.entry(f_name.to_owned()).or_default().extend(issues);
+    }
+
+    fn has_fail(&self) -> bool {
+        let lock = self.issues.lock().unwrap();
+        lock.values()
+            .flatten()
+            .any(|issue| issue.severity != "minor")
+    }
+
+    fn completion_message(&self) {
+        let lock = self.issues.lock().unwrap();
+        let issues = lock.values().flatten().collect_vec();
+        println!("{}", serde_json::to_string(&issues).unwrap());
+    }
+
+    fn dispatch_template_header(
+        &self,
+        _f_name: String,
+        _linter_config: FluffConfig,
+        _file_config: FluffConfig,
+    ) {
+    }
+
+    fn dispatch_parse_header(&self, _f_name: String) {}
+}
+
+#[derive(Serialize)]
+struct Issue {
+    description: String,
+    check_name: &'static str,
+    fingerprint: String,
+    severity: &'static str,
+    location: Location,
+}
+
+#[derive(Serialize)]
+struct Location {
+    path: String,
+    lines: Lines,
+}
+
+#[derive(Serialize)]
+struct Lines {
+    begin: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use sqruff_lib_core::errors::{ErrorStructRule, SQLBaseError};
+
+    use super::GitlabFormatter;
+
+    #[test]
+    fn test_gitlab_fingerprints() {
+        let violation = |line_no, source_slice| SQLBaseError {
+            line_no,
+            description: "Unexpected whitespace before comma.".into(),
+            rule: Some(ErrorStructRule {
+                name: "layout.spacing",
+                code: "LT01",
+            }),
+            source_slice,
+            ..SQLBaseError::default()
+        };
+
+        let before = GitlabFormatter::issues("a.sql", "SELECT a ,b", &[violation(1, 8..10)]);
+        // The same violation moved down a line, and a duplicate of it.
+        let after = GitlabFormatter::issues(
+            "a.sql",
+            "\nSELECT a ,b\nSELECT a ,b",
+            &[violation(2, 9..11), violation(3, 21..23)],
+        );
+
+        assert_eq!(before[0].fingerprint, after[0].fingerprint);
+        assert_ne!(after[0].fingerprint, after[1].fingerprint);
+
+        // Absolute paths, as found when linting without path arguments, are
+        // reported relative to the working directory.
+        let absolute = std::env::current_dir().unwrap().join("a.sql");
+        let absolute = GitlabFormatter::issues(
+            absolute.to_str().unwrap(),
+            "SELECT a ,b",
+            &[violation(1, 8..10)],
+        );
+        assert_eq!(absolute[0].location.path, "a.sql");
+        assert_eq!(absolute[0].fingerprint, before[0].fingerprint);
+        assert_eq!(after[1].location.lines.begin, 3);
+        assert_eq!(after[1].severity, "major");
+    }
+}
@@ -1,27 +1,58 @@
 use std::sync::Mutex;
 
+use sqruff_lib_core::errors::SQLBaseError;
+
 use crate::core::{config::FluffConfig, linter::linted_file::LintedFile};
 
 use super::{
     formatters::Formatter,
-    json_types::{Diagnostic, DiagnosticCollection, DiagnosticSeverity},
+    json_types::{
+        Diagnostic, DiagnosticCollection, DiagnosticSeverity, JsonReport, JSON_SCHEMA_VERSION,
+    },
 };
 
 #[derive(Default)]
 pub struct JsonFormatter {
     violations: Mutex<DiagnosticCollection>,
+    /// Whether to print the versioned `json-v2` report, whose diagnostics include their
+    /// rule, end position and fixes.
+    detailed: bool,
+}
+
+impl JsonFormatter {
+    pub fn detailed() -> Self {
+        Self {
+            detailed: true,
+            ..Self::default()
+        }
+    }
 }
 
 impl Formatter for JsonFormatter {
     fn dispatch_file_violations(&self, linted_file: &LintedFile, only_fixable: bool) {
         let violations = linted_file.get_violations(only_fixable.then_some(true));
         let mut lock = self.violations.lock().unwrap();
-        lock.entry(linted_file.path.clone()).or_default().extend(
-            violations
-                .iter()
-                .map(|err| Diagnostic::from(err.clone()))
-                .collect::<Vec<_>>(),
-        );
+        lock.entry(linted_file.path.clone())
+            .or_default()
+            .extend(violations.into_iter().map(|err| {
+                if self.detailed {
+                    Diagnostic::detailed(err, &linted_file.templated_file)
+                } else {
+                    Diagnostic::from(err)
+                }
+            }));
+    }
+
+    fn dispatch_file_warning(&self, f_name: &str, message: &str) {
+        let warning = SQLBaseError {
+            warning: true,
+            description: message.to_owned(),
+            ..SQLBaseError::default()
+        };
+        let mut lock = self.violations.lock().unwrap();
+        lock.entry(f_name.to_owned())
+            .or_default()
+            .push(Diagnostic::from(warning));
     }
 
     fn has_fail(&self) -> bool {
@@ -34,7 +65,15 @@ impl Formatter for JsonFormatter {
 
     fn completion_message(&self) {
         let lock = self.violations.lock().unwrap();
-        let json = serde_json::to_string(&*lock).unwrap();
+        let json = if self.detailed {
+            serde_json::to_string(&JsonReport {
+                version: JSON_SCHEMA_VERSION,
+                files: &lock,
+            })
+        } else {
+            serde_json::to_string(&*lock)
+        }
+        .unwrap();
         println!("{}", json);
     }
 
@@ -2,6 +2,48 @@ use std::collections::BTreeMap;
 
 use serde::Serialize;
 use sqruff_lib_core::errors::SQLBaseError;
+use sqruff_lib_core::templaters::base::TemplatedFile;
+
+/// The version of the `json-v2` output, bumped whenever its shape changes.
+pub const JSON_SCHEMA_VERSION: u32 = 2;
+
+impl Diagnostic {
+    /// Creates a diagnostic for the `json-v2` output, with its rule, end position and fixes
+    /// resolved against the source of `templated_file`.
+    pub fn detailed(value: SQLBaseError, templated_file: &TemplatedFile) -> Self {
+        let position = |offset| {
+            let (line, character) = templated_file.get_line_pos_of_char_pos(offset, true);
+            Position::new(line as u32, character as u32)
+        };
+
+        let end = (!value.source_slice.is_empty()).then(|| position(value.source_slice.end));
+        let fixes = value
+            .fixes
+            .iter()
+            .map(|fix| TextEdit {
+                range: Range {
+                    start: position(fix.source_slice.start),
+                    end: position(fix.source_slice.end),
+                },
+                new_text: fix.edit.clone(),
+            })
+            .collect();
+
+        let details = DiagnosticDetails {
+            code: value.rule.as_ref().map(|rule| rule.code),
+            name: value.rule.as_ref().map(|rule| rule.name),
+            fixable: value.fixable,
+            fixes,
+        };
+
+        let mut diagnostic = Diagnostic::from(value);
+        if let Some(end) = end {
+            diagnostic.range.end = end;
+        }
+        diagnostic.details = Some(details);
+        diagnostic
+    }
+}
 
 impl From<SQLBaseError> for Diagnostic {
     fn from(value: SQLBaseError) -> Self {
@@ -17,12 +59,7 @@ impl From<SQLBaseError> for Diagnostic {
                 DiagnosticSeverity::Error
             },
             source: Some("sqruff".to_string()),
-            // code: todo!(),
-            // source: Some(value.get_source().to_string()),
-            // code: Some(DiagnosticCode {
-            //     value: value.rule_code().to_string(),
-            //     target: Uri::new("".to_string()),
-            // }),
+            details: None,
             // related_information: Vec::new(),
             // tags: Vec::new(),
         }
@@ -65,8 +102,9 @@ pub struct Diagnostic {
     pub severity: DiagnosticSeverity,
     /// A human-readable string describing the source of this diagnostic, e.g. 'typescript' or 'super lint'.
     source: Option<String>,
-    // A code or identifier for this diagnostic. Should be used for later processing, e.g. when providing {@link CodeActionContext code actions}.
-    // code: Option<DiagnosticCode>,
+    /// The rule and fixes of this diagnostic, only included in the `json-v2` output.
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    details: Option<DiagnosticDetails>,
     // TODO Maybe implement
     // An array of related diagnostic information, e.g. when symbol-names within a scope collide all definitions can be marked via this property.
     // related_information: Vec<DiagnosticRelatedInformation>,
@@ -74,6 +112,29 @@ pub struct Diagnostic {
     // tags: Vec<DiagnosticTag>,
 }
 
+/// The rule which raised a diagnostic and the edits which fix it.
+#[derive(Serialize)]
+struct DiagnosticDetails {
+    /// The code of the rule which raised this diagnostic, e.g. `LT01`.
+    code: Option<&'static str>,
+    /// The name of the rule which raised this diagnostic, e.g. `layout.spacing`.
+    name: Option<&'static str>,
+    /// Whether the rule which raised this diagnostic can fix it.
+    fixable: bool,
+    /// The edits to the source which fix this diagnostic.
+    fixes: Vec<TextEdit>,
+}
+
+/// A textual edit applicable to a file.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TextEdit {
+    /// The range of the text to be replaced. An empty range means an insertion.
+    range: Range,
+    /// The string to be inserted. An empty string means a deletion.
+    new_text: String,
+}
+
 // /// Represents a related message and source code location for a diagnostic. This should be used to point to code locations that cause or are related to a diagnostics, e.g when duplicating a symbol in a scope.
 // #[derive(Serialize)]
 // struct DiagnosticCode {
@@ -98,3 +159,12 @@ pub enum DiagnosticSeverity {
 }
 
 pub type DiagnosticCollection = BTreeMap<String, Vec<Diagnostic>>;
+
+/// The output of the JSON formatter in the `json-v2` format.
+#[derive(Serialize)]
+pub struct JsonReport<'a> {
+    /// See [`JSON_SCHEMA_VERSION`].
+    pub version: u32,
+    /// The diagnostics of each file, keyed by path.
+    pub files: &'a DiagnosticCollection,
+}
@@ -0,0 +1,441 @@
+use std::sync::Mutex;
+
+use serde::Serialize;
+use sqruff_lib_core::errors::SQLBaseError;
+use sqruff_lib_core::templaters::base::TemplatedFile;
+
+use super::formatters::Formatter;
+use crate::core::config::FluffConfig;
+use crate::core::linter::linted_file::LintedFile;
+use crate::core::rules::base::RuleSet;
+use crate::rules::get_ruleset;
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const INFORMATION_URI: &str = "https://github.com/quarylabs/sqruff";
+
+/// Codes which are reported by the linter itself rather than by a rule, with
+/// their names and descriptions.
+const PSEUDO_RULES: [(&str, &str, &str); 4] = [
+    ("TMP", "templating", "The file could not be templated."),
+    ("CFG", "inline_config", "Invalid inline configuration."),
+    ("NOQA", "noqa", "Unused or invalid noqa comment."),
+    (
+        "LOOP",
+        "fix_loop",
+        "Fixing did not settle within the configured number of loops.",
+    ),
+];
+
+/// Collects violations and prints them as a [SARIF 2.1.0](https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html)
+/// log once linting has finished.
+#[derive(Default)]
+pub struct SarifFormatter {
+    results: Mutex<Vec<SarifResult>>,
+    notifications: Mutex<Vec<Notification>>,
+}
+
+impl SarifFormatter {
+    fn result(violation: SQLBaseError, uri: &str, templated_file: &TemplatedFile) -> SarifResult {
+        let source = templated_file.source_str.as_str();
+        let position = |char_pos| {
+            let (line_no, line_pos) = templated_file.get_line_pos_of_char_pos(char_pos, true);
+            (line_no, code_point_column(source, line_no, line_pos))
+        };
+
+        let region = (violation.line_no > 0).then(|| {
+            let end = (violation.source_slice.end > violation.source_slice.start)
+                .then(|| position(violation.source_slice.end));
+            Region {
+                start_line: violation.line_no,
+                start_column: code_point_column(source, violation.line_no, violation.line_pos),
+                end_line: end.map(|(line, _)| line),
+                end_column: end.map(|(_, column)| column),
+            }
+        });
+
+        let fixes = if violation.fixes.is_empty() {
+            Vec::new()
+        } else {
+            let replacements = violation
+                .fixes
+                .iter()
+                .map(|fix| {
+                    let (start_line, start_column) = position(fix.source_slice.start);
+                    let (end_line, end_column) = position(fix.source_slice.end);
+                    Replacement {
+                        deleted_region: Region {
+                            start_line,
+                            start_column,
+                            end_line: Some(end_line),
+                            end_column: Some(end_column),
+                        },
+                        inserted_content: Message {
+                            text: fix.edit.clone(),
+                        },
+                    }
+                })
+                .collect();
+
+            vec![Fix {
+                artifact_changes: vec![ArtifactChange {
+                    artifact_location: ArtifactLocation::new(uri),
+                    replacements,
+                }],
+            }]
+        };
+
+        SarifResult {
+            rule_id: violation.rule.as_ref().map(|rule| rule.code),
+            // Filled in once all results are known, see `completion_message`.
+            rule_index: None,
+            level: if violation.warning {
+                "warning"
+            } else {
+                "error"
+            },
+            message: Message {
+                text: violation.description,
+            },
+            locations: vec![Location {
+                physical_location: PhysicalLocation {
+                    artifact_location: ArtifactLocation::new(uri),
+                    region,
+                },
+            }],
+            fixes,
+        }
+    }
+
+    fn rules(ruleset: &RuleSet) -> Vec<ReportingDescriptor> {
+        ruleset
+            .register
+            .values()
+            .map(|manifest| ReportingDescriptor {
+                id: manifest.code,
+                name: manifest.name,
+                short_description: Message {
+                    text: manifest.description.to_string(),
+                },
+                full_description: Message {
+                    text: manifest.rule_class.long_description().to_string(),
+                },
+                properties: RuleProperties {
+                    tags: manifest.groups.iter().map(AsRef::as_ref).collect(),
+                },
+            })
+            .chain(
+                PSEUDO_RULES
+                    .iter()
+                    .map(|&(code, name, description)| ReportingDescriptor {
+                        id: code,
+                        name,
+                        short_description: Message {
+                            text: description.to_owned(),
+                        },
+                        full_description: Message {
+                            text: description.to_owned(),
+                        },
+                        properties: RuleProperties { tags: Vec::new() },
+                    }),
+            )
+            .collect()
+    }
+
+    /// The index of a code in the rules of the driver, see [`Self::rules`].
+    fn rule_index(ruleset: &RuleSet, code: &str) -> Option<usize> {
+        ruleset.register.get_index_of(code).or_else(|| {
+            PSEUDO_RULES
+                .iter()
+                .position(|&(pseudo, ..)| pseudo == code)
+                .map(|index| ruleset.register.len() + index)
+        })
+    }
+}
+
+/// Convert a byte based column on a line of the source into a column in
+/// Unicode code points, which is the `columnKind` of the log.
+fn code_point_column(source: &str, line_no: usize, line_pos: usize) -> usize {
+    let line = source.split('\n').nth(line_no - 1).unwrap_or_default();
+    let end = line_pos.saturating_sub(1).min(line.len());
+    line.get(..end).map_or(end, |prefix| prefix.chars().count()) + 1
+}
+
+impl Formatter for SarifFormatter {
+    fn dispatch_file_violations(&self, linted_file: &LintedFile, only_fixable: bool) {
+        let uri = linted_file.path.replace('\\', "/");
+        let violations = linted_file.get_violations(only_fixable.then_some(true));
+        let mut lock = self.results.lock().unwrap();
+        lock.extend(
+            violations
+                .into_iter()
+                .map(|violation| Self::result(violation, &uri, &linted_file.templated_file)),
+        );
+    }
+
+    fn dispatch_file_warning(&self, f_name: &str, message: &str) {
+        let mut lock = self.notifications.lock().unwrap();
+        lock.push(Notification {
+            level: "warning",
+            message: Message {
+                text: message.to_owned(),
+            },
+            locations: vec![Location {
+                physical_location: PhysicalLocation {
+                    artifact_location: ArtifactLocation::new(&f_name.replace('\\', "/")),
+                    region: None,
+                },
+            }],
+        });
+    }
+
+    fn has_fail(&self) -> bool {
+        let lock = self.results.lock().unwrap();
+        lock.iter().any(|result| result.level == "error")
+    }
+
+    fn completion_message(&self) {
+        let ruleset = get_ruleset();
+        let mut results = self.results.lock().unwrap();
+        for result in results.iter_mut() {
+            result.rule_index = result
+                .rule_id
+                .and_then(|code| Self::rule_index(&ruleset, code));
+        }
+        let notifications = self.notifications.lock().unwrap();
+
+        let log = SarifLog {
+            schema: SCHEMA,
+            version: "2.1.0",
+            runs: vec![Run {
+                tool: Tool {
+                    driver: ToolComponent {
+                        name: "sqruff",
+                        version: env!("CARGO_PKG_VERSION"),
+                        information_uri: INFORMATION_URI,
+                        rules: Self::rules(&ruleset),
+                    },
+                },
+                invocations: vec![Invocation {
+                    execution_successful: true,
+                    tool_execution_notifications: &notifications,
+                }],
+                column_kind: "unicodeCodePoints",
+                results: &results,
+            }],
+        };
+
+        println!("{}", serde_json::to_string(&log).unwrap());
+    }
+
+    fn dispatch_template_header(
+        &self,
+        _f_name: String,
+        _linter_config: FluffConfig,
+        _file_config: FluffConfig,
+    ) {
+    }
+
+    fn dispatch_parse_header(&self, _f_name: String) {}
+}
+
+#[derive(Serialize)]
+struct SarifLog<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<Run<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Run<'a> {
+    tool: Tool,
+    invocations: Vec<Invocation<'a>>,
+    column_kind: &'static str,
+    results: &'a [SarifResult],
+}
+
+#[derive(Serialize)]
+struct Tool {
+    driver: ToolComponent,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolComponent {
+    name: &'static str,
+    version: &'static str,
+    information_uri: &'static str,
+    rules: Vec<ReportingDescriptor>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReportingDescriptor {
+    id: &'static str,
+    name: &'static str,
+    short_description: Message,
+    full_description: Message,
+    properties: RuleProperties,
+}
+
+#[derive(Serialize)]
+struct RuleProperties {
+    tags: Vec<&'static str>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Invocation<'a> {
+    execution_successful: bool,
+    tool_execution_notifications: &'a [Notification],
+}
+
+#[derive(Serialize)]
+struct Notification {
+    level: &'static str,
+    message: Message,
+    locations: Vec<Location>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_id: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_index: Option<usize>,
+    level: &'static str,
+    message: Message,
+    locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fixes: Vec<Fix>,
+}
+
+#[derive(Serialize)]
+struct Message {
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    physical_location: PhysicalLocation,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    region: Option<Region>,
+}
+
+#[derive(Serialize)]
+struct ArtifactLocation {
+    uri: String,
+}
+
+impl ArtifactLocation {
+    fn new(uri: &str) -> Self {
+        Self {
+            uri: uri.to_owned(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    start_line: usize,
+    start_column: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_column: Option<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Fix {
+    artifact_changes: Vec<ArtifactChange>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactChange {
+    artifact_location: ArtifactLocation,
+    replacements: Vec<Replacement>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Replacement {
+    deleted_region: Region,
+    inserted_content: Message,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::SarifFormatter;
+    use crate::cli::formatters::Formatter;
+    use crate::core::config::FluffConfig;
+    use crate::core::linter::core::Linter;
+    use crate::rules::get_ruleset;
+
+    #[test]
+    fn test_sarif_results() {
+        let formatter = Arc::new(SarifFormatter::default());
+        let config = FluffConfig::from_source("[sqruff]\nrules = LT01\n");
+        let linter = Linter::new(config, Some(formatter.clone()), None);
+        linter.lint_string("SELECT   1\n", None, false);
+
+        assert!(formatter.has_fail());
+
+        let results = serde_json::to_value(&*formatter.results.lock().unwrap()).unwrap();
+        let result = &results[0];
+        assert_eq!(result["ruleId"], "LT01");
+        assert_eq!(result["level"], "error");
+
+        let region = &result["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(region["startLine"], 1);
+        assert_eq!(region["startColumn"], 7);
+        assert_eq!(region["endColumn"], 10);
+
+        let replacement = &result["fixes"][0]["artifactChanges"][0]["replacements"][0];
+        assert_eq!(replacement["deletedRegion"]["startColumn"], 7);
+        assert_eq!(replacement["deletedRegion"]["endColumn"], 10);
+        assert_eq!(replacement["insertedContent"]["text"], " ");
+    }
+
+    #[test]
+    fn test_sarif_columns_are_code_points() {
+        let formatter = Arc::new(SarifFormatter::default());
+        let config = FluffConfig::from_source("[sqruff]\nrules = LT01\n");
+        let linter = Linter::new(config, Some(formatter.clone()), None);
+        linter.lint_string("SELECT 'é',   1\n", None, false);
+
+        let results = serde_json::to_value(&*formatter.results.lock().unwrap()).unwrap();
+        let region = &results[0]["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(region["startColumn"], 12);
+        assert_eq!(region["endColumn"], 15);
+
+        let replacement = &results[0]["fixes"][0]["artifactChanges"][0]["replacements"][0];
+        assert_eq!(replacement["deletedRegion"]["startColumn"], 12);
+        assert_eq!(replacement["deletedRegion"]["endColumn"], 15);
+    }
+
+    #[test]
+    fn test_sarif_pseudo_rules() {
+        let ruleset = get_ruleset();
+        let rules = SarifFormatter::rules(&ruleset);
+
+        for code in ["TMP", "CFG", "NOQA", "LOOP"] {
+            let index = SarifFormatter::rule_index(&ruleset, code).unwrap();
+            assert_eq!(rules[index].id, code);
+        }
+        assert_eq!(SarifFormatter::rule_index(&ruleset, "XX01"), None);
+    }
+}
@@ -0,0 +1,300 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::sync::Mutex;
+
+use itertools::Itertools;
+use sqruff_lib_core::errors::SQLBaseError;
+
+use super::formatters::Formatter;
+use crate::core::config::FluffConfig;
+use crate::core::linter::linted_file::LintedFile;
+
+/// Violations collected per file, in the order of their paths.
+#[derive(Default)]
+struct FileViolations(Mutex<BTreeMap<String, Vec<SQLBaseError>>>);
+
+impl FileViolations {
+    fn dispatch_file_violations(&self, linted_file: &LintedFile, only_fixable: bool) {
+        let violations = linted_file.get_violations(only_fixable.then_some(true));
+        let mut lock = self.0.lock().unwrap();
+        lock.entry(linted_file.path.clone())
+            .or_default()
+            .extend(violations);
+    }
+
+    fn dispatch_file_warning(&self, f_name: &str, message: &str) {
+        let warning = SQLBaseError {
+            warning: true,
+            description: message.to_owned(),
+            ..SQLBaseError::default()
+        };
+        let mut lock = self.0.lock().unwrap();
+        lock.entry(f_name.to_owned()).or_default().push(warning);
+    }
+
+    fn has_fail(&self) -> bool {
+        let lock = self.0.lock().unwrap();
+        lock.values().flatten().any(is_failure)
+    }
+}
+
+fn is_failure(violation: &SQLBaseError) -> bool {
+    !violation.ignore && !violation.warning
+}
+
+/// Escape text for use in XML attributes and element content.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for char in text.chars() {
+        match char {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' => escaped.push_str("&#10;"),
+            _ => escaped.push(char),
+        }
+    }
+    escaped
+}
+
+/// Prints a JUnit XML report once linting has finished, with a test case per
+/// file which fails if the file has any violations which aren't warnings.
+#[derive(Default)]
+pub struct JUnitFormatter {
+    files: FileViolations,
+}
+
+impl JUnitFormatter {
+    fn report(files: &BTreeMap<String, Vec<SQLBaseError>>) -> String {
+        let tests = files.len();
+        let failures = files
+            .values()
+            .filter(|violations| violations.iter().any(is_failure))
+            .count();
+
+        let mut report = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        _ = writeln!(
+            report,
+            "<testsuites name=\"sqruff\" tests=\"{tests}\" failures=\"{failures}\" errors=\"0\">"
+        );
+        _ = writeln!(
+            report,
+            "  <testsuite name=\"sqruff\" tests=\"{tests}\" failures=\"{failures}\" errors=\"0\">"
+        );
+
+        for (path, violations) in files {
+            let path = escape(path);
+            let (errors, warnings): (Vec<_>, Vec<_>) = violations
+                .iter()
+                .partition(|violation| is_failure(violation));
+
+            if violations.is_empty() {
+                _ = writeln!(
+                    report,
+                    "    <testcase classname=\"sqruff\" name=\"{path}\" file=\"{path}\"/>"
+                );
+                continue;
+            }
+
+            _ = writeln!(
+                report,
+                "    <testcase classname=\"sqruff\" name=\"{path}\" file=\"{path}\">"
+            );
+            if !errors.is_empty() {
+                let codes = errors
+                    .iter()
+                    .map(|violation| violation.rule_code())
+                    .collect::<BTreeSet<_>>();
+                _ = writeln!(
+                    report,
+                    "      <failure type=\"{}\" message=\"{} violation(s) found\">{}</failure>",
+                    escape(&codes.into_iter().join(",")),
+                    errors.len(),
+                    Self::lines(&errors)
+                );
+            }
+            if !warnings.is_empty() {
+                _ = writeln!(
+                    report,
+                    "      <system-out>{}</system-out>",
+                    Self::lines(&warnings)
+                );
+            }
+            report.push_str("    </testcase>\n");
+        }
+
+        report.push_str("  </testsuite>\n</testsuites>");
+        report
+    }
+
+    /// One line per violation, like `L:   1 | P:   7 | LT01 | message`.
+    fn lines(violations: &[&SQLBaseError]) -> String {
+        violations
+            .iter()
+            .map(|violation| {
+                escape(&format!(
+                    "L:{:4} | P:{:4} | {} | {}\n",
+                    violation.line_no,
+                    violation.line_pos,
+                    violation.rule_code(),
+                    violation.desc()
+                ))
+            })
+            .collect()
+    }
+}
+
+impl Formatter for JUnitFormatter {
+    fn dispatch_file_violations(&self, linted_file: &LintedFile, only_fixable: bool) {
+        self.files
+            .dispatch_file_violations(linted_file, only_fixable);
+    }
+
+    fn dispatch_file_warning(&self, f_name: &str, message: &str) {
+        self.files.dispatch_file_warning(f_name, message);
+    }
+
+    fn has_fail(&self) -> bool {
+        self.files.has_fail()
+    }
+
+    fn completion_message(&self) {
+        println!("{}", Self::report(&self.files.0.lock().unwrap()));
+    }
+
+    fn dispatch_template_header(
+        &self,
+        _f_name: String,
+        _linter_config: FluffConfig,
+        _file_config: FluffConfig,
+    ) {
+    }
+
+    fn dispatch_parse_header(&self, _f_name: String) {}
+}
+
+/// Prints a Checkstyle XML report once linting has finished.
+#[derive(Default)]
+pub struct CheckstyleFormatter {
+    files: FileViolations,
+}
+
+impl CheckstyleFormatter {
+    fn report(files: &BTreeMap<String, Vec<SQLBaseError>>) -> String {
+        let mut report = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        report.push_str("<checkstyle version=\"4.3\">\n");
+
+        for (path, violations) in files {
+            _ = writeln!(report, "  <file name=\"{}\">", escape(path));
+            for violation in violations {
+                let severity = if is_failure(violation) {
+                    "error"
+                } else {
+                    "warning"
+                };
+                report.push_str("    <error");
+                if violation.line_no > 0 {
+                    _ = write!(
+                        report,
+                        " line=\"{}\" column=\"{}\"",
+                        violation.line_no, violation.line_pos
+                    );
+                }
+                _ = writeln!(
+                    report,
+                    " severity=\"{severity}\" message=\"{}\" source=\"sqruff.{}\"/>",
+                    escape(violation.desc()),
+                    escape(violation.rule_code())
+                );
+            }
+            report.push_str("  </file>\n");
+        }
+
+        report.push_str("</checkstyle>");
+        report
+    }
+}
+
+impl Formatter for CheckstyleFormatter {
+    fn dispatch_file_violations(&self, linted_file: &LintedFile, only_fixable: bool) {
+        self.files
+            .dispatch_file_violations(linted_file, only_fixable);
+    }
+
+    fn dispatch_file_warning(&self, f_name: &str, message: &str) {
+        self.files.dispatch_file_warning(f_name, message);
+    }
+
+    fn has_fail(&self) -> bool {
+        self.files.has_fail()
+    }
+
+    fn completion_message(&self) {
+        println!("{}", Self::report(&self.files.0.lock().unwrap()));
+    }
+
+    fn dispatch_template_header(
+        &self,
+        _f_name: String,
+        _linter_config: FluffConfig,
+        _file_config: FluffConfig,
+    ) {
+    }
+
+    fn dispatch_parse_header(&self, _f_name: String) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use sqruff_lib_core::errors::{ErrorStructRule, SQLBaseError};
+
+    use super::{CheckstyleFormatter, JUnitFormatter};
+
+    fn files() -> BTreeMap<String, Vec<SQLBaseError>> {
+        let violation = |code, warning| SQLBaseError {
+            warning,
+            line_no: 1,
+            line_pos: 7,
+            description: "Expected \"1\" & <more>.".into(),
+            rule: Some(ErrorStructRule { name: "", code }),
+            ..SQLBaseError::default()
+        };
+
+        BTreeMap::from([
+            (
+                "a.sql".into(),
+                vec![violation("LT01", false), violation("CP01", true)],
+            ),
+            ("b.sql".into(), Vec::new()),
+        ])
+    }
+
+    #[test]
+    fn test_junit_report() {
+        let report = JUnitFormatter::report(&files());
+
+        assert!(report.contains("<testsuites name=\"sqruff\" tests=\"2\" failures=\"1\""));
+        assert!(report.contains(
+            "<failure type=\"LT01\" message=\"1 violation(s) found\">L:   1 | P:   7 | LT01 | \
+             Expected &quot;1&quot; &amp; &lt;more&gt;.&#10;</failure>"
+        ));
+        assert!(report.contains("<system-out>L:   1 | P:   7 | CP01 |"));
+        assert!(report.contains("<testcase classname=\"sqruff\" name=\"b.sql\" file=\"b.sql\"/>"));
+    }
+
+    #[test]
+    fn test_checkstyle_report() {
+        let report = CheckstyleFormatter::report(&files());
+
+        assert!(report.contains(
+            "<error line=\"1\" column=\"7\" severity=\"error\" message=\"Expected &quot;1&quot; \
+             &amp; &lt;more&gt;.\" source=\"sqruff.LT01\"/>"
+        ));
+        assert!(report.contains("severity=\"warning\""));
+        assert!(report.contains("<file name=\"b.sql\">\n  </file>"));
+    }
+}
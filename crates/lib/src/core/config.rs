@@ -1,13 +1,14 @@
 use std::ops::Index;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::Arc;
 
 use ahash::AHashMap;
 use configparser::ini::Ini;
 use itertools::Itertools;
 use sqruff_lib_core::dialects::base::Dialect;
 use sqruff_lib_core::dialects::init::{dialect_readout, DialectKind};
-use sqruff_lib_core::errors::SQLFluffUserError;
+use sqruff_lib_core::errors::{ErrorStructRule, SQLBaseError, SQLFluffUserError};
 use sqruff_lib_core::parser::parser::Parser;
 use sqruff_lib_dialects::kind_to_dialect;
 
@@ -34,9 +35,14 @@ pub struct FluffConfig {
     pub raw: AHashMap<String, Value>,
     extra_config_path: Option<String>,
     _configs: AHashMap<String, AHashMap<String, String>>,
-    pub(crate) dialect: Dialect,
+    pub(crate) dialect: Arc<Dialect>,
     sql_file_exts: Vec<String>,
     reflow: ReflowConfig,
+    /// Values of the core section which take precedence over any config file.
+    overrides: AHashMap<String, Value>,
+    /// Whether the config was given explicitly, in which case config files
+    /// next to the linted files are ignored.
+    explicit: bool,
 }
 
 impl Default for FluffConfig {
@@ -50,6 +56,16 @@ impl FluffConfig {
         &self.raw[section][key]
     }
 
+    /// Set a value in a section of the config, overriding any loaded value.
+    pub fn set_value(&mut self, key: &str, section: &str, value: Value) {
+        self.raw
+            .entry(section.to_owned())
+            .or_insert_with(|| Value::Map(AHashMap::new()))
+            .as_map_mut()
+            .unwrap()
+            .insert(key.to_owned(), value);
+    }
+
     pub fn reflow(&self) -> &ReflowConfig {
         &self.reflow
     }
@@ -58,8 +74,14 @@ impl FluffConfig {
         self.reflow = ReflowConfig::from_fluff_config(self);
     }
 
+    /// Load a config from the contents of a config file.
+    ///
+    /// # Panics
+    ///
+    /// If `source` can't be parsed. Use [`ConfigLoader::from_source`] to
+    /// handle the error instead.
     pub fn from_source(source: &str) -> FluffConfig {
-        let configs = ConfigLoader {}.from_source(source);
+        let configs = ConfigLoader {}.from_source(source).unwrap();
 
         FluffConfig::new(configs, None, None)
     }
@@ -74,31 +96,14 @@ impl FluffConfig {
         extra_config_path: Option<String>,
         indentation: Option<FluffConfigIndentation>,
     ) -> Self {
-        fn nested_combine(
-            mut a: AHashMap<String, Value>,
-            b: AHashMap<String, Value>,
-        ) -> AHashMap<String, Value> {
-            for (key, value_b) in b {
-                match (a.get(&key), value_b) {
-                    (Some(Value::Map(map_a)), Value::Map(map_b)) => {
-                        let combined = nested_combine(map_a.clone(), map_b);
-                        a.insert(key, Value::Map(combined));
-                    }
-                    (_, value) => {
-                        a.insert(key, value);
-                    }
-                }
-            }
-            a
-        }
-
         let values = ConfigLoader
-            .get_config_elems_from_file(None, include_str!("./default_config.cfg").into());
+            .get_config_elems_from_file(None, include_str!("./default_config.cfg").into())
+            .unwrap();
 
         let mut defaults = AHashMap::new();
         ConfigLoader.incorporate_vals(&mut defaults, values);
 
-        let mut configs = nested_combine(defaults, configs);
+        let mut configs = combine(defaults, configs);
 
         let dialect = match configs
             .get("core")
@@ -118,19 +123,16 @@ impl FluffConfig {
             // Allowlists and denylistsignore_words
             ("exclude_rules", "rule_denylist"),
         ] {
-            match configs["core"].as_map().unwrap().get(in_key) {
-                Some(value) if !value.is_none() => {
-                    let string = value.as_string().unwrap();
-                    let values = split_comma_separated_string(string);
-
-                    configs
-                        .get_mut("core")
-                        .unwrap()
-                        .as_map_mut()
-                        .unwrap()
-                        .insert(out_key.into(), values);
-                }
-                _ => {}
+            // Values are only strings until they have been split once.
+            if let Some(Value::String(string)) = configs["core"].as_map().unwrap().get(in_key) {
+                let values = split_comma_separated_string(string);
+
+                configs
+                    .get_mut("core")
+                    .unwrap()
+                    .as_map_mut()
+                    .unwrap()
+                    .insert(out_key.into(), values);
             }
         }
 
@@ -143,13 +145,16 @@ impl FluffConfig {
 
         let mut this = Self {
             raw: configs,
-            dialect: dialect
-                .expect("Dialect is disabled. Please enable the corresponding feature."),
+            dialect: Arc::new(
+                dialect.expect("Dialect is disabled. Please enable the corresponding feature."),
+            ),
             extra_config_path,
             _configs: AHashMap::new(),
             indentation: indentation.unwrap_or_default(),
             sql_file_exts,
             reflow: ReflowConfig::default(),
+            overrides: AHashMap::new(),
+            explicit: false,
         };
         this.reflow = ReflowConfig::from_fluff_config(&this);
         this
@@ -160,6 +165,13 @@ impl FluffConfig {
         self
     }
 
+    /// Mark the config as given explicitly, e.g. with `--config`, so that it
+    /// applies to every file as is.
+    pub fn explicit(mut self) -> Self {
+        self.explicit = true;
+        self
+    }
+
     /// Loads a config object just based on the root directory.
     // TODO This is not a translation that is particularly accurate.
     pub fn from_root(
@@ -168,22 +180,76 @@ impl FluffConfig {
         overrides: Option<AHashMap<String, String>>,
     ) -> Result<FluffConfig, SQLFluffUserError> {
         let loader = ConfigLoader {};
+        let config =
+            loader.load_config_up_to_path(".", extra_config_path.clone(), ignore_local_config)?;
+
+        let overrides = parse_overrides(overrides.unwrap_or_default());
         let mut config =
-            loader.load_config_up_to_path(".", extra_config_path.clone(), ignore_local_config);
+            FluffConfig::new(apply_overrides(config, &overrides), extra_config_path, None);
+        config.overrides = overrides;
 
-        if let Some(overrides) = overrides {
-            if let Some(dialect) = overrides.get("dialect") {
-                let core = config
-                    .entry("core".into())
-                    .or_insert_with(|| Value::Map(AHashMap::new()));
+        Ok(config)
+    }
 
-                core.as_map_mut()
-                    .unwrap()
-                    .insert("dialect".into(), Value::String(dialect.clone().into()));
-            }
+    /// Override values of the core section, e.g. from command line options.
+    /// The overrides also take precedence over config files found later by
+    /// [`FluffConfig::make_child_from_path`].
+    pub fn with_overrides(self, overrides: AHashMap<String, String>) -> FluffConfig {
+        let mut all_overrides = self.overrides;
+        all_overrides.extend(parse_overrides(overrides));
+
+        let mut config = FluffConfig::new(
+            apply_overrides(self.raw, &all_overrides),
+            self.extra_config_path,
+            Some(self.indentation),
+        )
+        .with_sql_file_exts(self.sql_file_exts);
+        config.overrides = all_overrides;
+        config.explicit = self.explicit;
+        config
+    }
+
+    /// Make the config for a path, layering any config files found between the
+    /// working directory and the path on top of this config.
+    pub fn make_child_from_path(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<FluffConfig, SQLFluffUserError> {
+        if self.explicit {
+            return Ok(self.clone());
+        }
+
+        let loader = ConfigLoader;
+        let working_path = std::env::current_dir()
+            .and_then(|path| path.canonicalize())
+            .ok();
+
+        // Config files in the working directory are already part of this config.
+        let config_stack =
+            ConfigLoader::iter_config_locations_up_to_path(path.as_ref(), None, false)
+                .filter(|location| Some(location) != working_path.as_ref())
+                .map(|location| loader.load_config_at_path(location))
+                .filter_ok(|configs| !configs.is_empty())
+                .collect::<Result<Vec<_>, _>>()?;
+
+        if config_stack.is_empty() {
+            return Ok(self.clone());
         }
 
-        Ok(FluffConfig::new(config, extra_config_path, None))
+        let mut configs = config_stack.into_iter().fold(self.raw.clone(), combine);
+        // An explicitly given config file overrides any local config.
+        if let Some(extra_config_path) = &self.extra_config_path {
+            configs = combine(configs, loader.load_config_at_path(extra_config_path)?);
+        }
+
+        let mut config = FluffConfig::new(
+            apply_overrides(configs, &self.overrides),
+            self.extra_config_path.clone(),
+            Some(self.indentation.clone()),
+        )
+        .with_sql_file_exts(self.sql_file_exts.clone());
+        config.overrides = self.overrides.clone();
+        Ok(config)
     }
 
     pub fn from_kwargs(
@@ -202,19 +268,118 @@ impl FluffConfig {
     }
 
     /// Process a full raw file for inline config and update self.
-    pub fn process_raw_file_for_config(&self, raw_str: &str) {
+    ///
+    /// Commands which can't be applied are skipped, and returned as violations
+    /// on their lines.
+    pub fn process_raw_file_for_config(&mut self, raw_str: &str) -> Vec<SQLBaseError> {
+        let mut changed = false;
+        let mut violations = Vec::new();
+
         // Scan the raw file for config commands
-        for raw_line in raw_str.lines() {
-            if raw_line.to_string().starts_with("-- sqlfluff") {
-                // Found an in-file config command
-                self.process_inline_config(raw_line)
+        let mut line_start = 0;
+        for (line_index, raw_line) in raw_str.split_inclusive('\n').enumerate() {
+            let line_range = line_start..line_start + raw_line.trim_end().len();
+            line_start += raw_line.len();
+
+            if !Self::is_inline_config(raw_line) {
+                continue;
+            }
+
+            // Found an in-file config command
+            match self.process_inline_config(raw_line.trim_end()) {
+                Ok(line_changed) => changed |= line_changed,
+                Err(error) => violations.push(SQLBaseError {
+                    fatal: false,
+                    ignore: false,
+                    warning: false,
+                    line_no: line_index + 1,
+                    line_pos: 1,
+                    description: error.value,
+                    rule: Some(ErrorStructRule {
+                        name: "inline_config",
+                        code: "CFG",
+                    }),
+                    source_slice: line_range,
+                    fixable: false,
+                    fixes: Vec::new(),
+                }),
             }
         }
+
+        if changed {
+            let sql_file_exts = std::mem::take(&mut self.sql_file_exts);
+            let overrides = std::mem::take(&mut self.overrides);
+            let explicit = self.explicit;
+            *self = FluffConfig::new(
+                std::mem::take(&mut self.raw),
+                self.extra_config_path.take(),
+                Some(self.indentation.clone()),
+            )
+            .with_sql_file_exts(sql_file_exts);
+            self.overrides = overrides;
+            self.explicit = explicit;
+        }
+
+        violations
+    }
+
+    /// Whether a line of a raw file is an inline config command.
+    pub fn is_inline_config(raw_line: &str) -> bool {
+        raw_line.starts_with("-- sqlfluff:") || raw_line.starts_with("-- sqruff:")
     }
 
     /// Process an inline config command and update self.
-    pub fn process_inline_config(&self, _config_line: &str) {
-        panic!("Not implemented")
+    ///
+    /// Commands take the form `-- sqruff:dialect:postgres`. Keys outside the
+    /// `core` section are addressed by their full path, for example
+    /// `-- sqruff:rules:capitalisation.keywords:capitalisation_policy:upper`.
+    /// As in config files, section names are lowercased and keys keep their
+    /// case. Returns whether the config was changed.
+    pub fn process_inline_config(&mut self, config_line: &str) -> Result<bool, SQLFluffUserError> {
+        let Some(config_line) = config_line
+            .strip_prefix("-- sqlfluff:")
+            .or_else(|| config_line.strip_prefix("-- sqruff:"))
+        else {
+            return Ok(false);
+        };
+
+        let mut config_path = config_line.split(':').map(str::trim).collect_vec();
+        if config_path.len() < 2 {
+            return Ok(false);
+        }
+
+        let value = config_path.pop().unwrap();
+        if config_path.len() == 1 {
+            config_path.insert(0, "core");
+        }
+
+        if config_path == ["core", "dialect"] && DialectKind::from_str(value).is_err() {
+            return Err(SQLFluffUserError::new(format!(
+                "Error loading dialect '{value}' from inline config. Available dialects: {}",
+                dialect_readout().join(", ")
+            )));
+        }
+
+        let (last_key, sections) = config_path.split_last().unwrap();
+        let mut current_map = &mut self.raw;
+        for key in sections {
+            match current_map
+                .entry(key.to_lowercase())
+                .or_insert_with(|| Value::Map(AHashMap::new()))
+                .as_map_mut()
+            {
+                Some(slot) => current_map = slot,
+                None => {
+                    return Err(SQLFluffUserError::new(format!(
+                        "Inline config command overrides the value {key:?} with a section: \
+                         {config_line:?}"
+                    )))
+                }
+            }
+        }
+        current_map.insert((*last_key).to_owned(), value.parse().unwrap());
+
+        Ok(true)
     }
 
     /// Check if the config specifies a dialect, raising an error if not.
@@ -318,7 +483,8 @@ impl ConfigLoader {
             }
         });
 
-        head.chain(tail)
+        // Configs closer to the given path take precedence, so they come last.
+        tail.chain(head)
     }
 
     pub fn load_config_up_to_path(
@@ -326,30 +492,38 @@ impl ConfigLoader {
         path: impl AsRef<Path>,
         extra_config_path: Option<String>,
         ignore_local_config: bool,
-    ) -> AHashMap<String, Value> {
+    ) -> Result<AHashMap<String, Value>, SQLFluffUserError> {
         let path = path.as_ref();
 
         let config_stack = if ignore_local_config {
             extra_config_path
-                .map(|path| vec![self.load_config_at_path(path)])
-                .unwrap_or_default()
+                .map(|path| self.load_config_at_path(path))
+                .into_iter()
+                .collect::<Result<Vec<_>, _>>()?
         } else {
             let configs = Self::iter_config_locations_up_to_path(path, None, ignore_local_config);
             configs
                 .map(|path| self.load_config_at_path(path))
-                .collect_vec()
+                .collect::<Result<Vec<_>, _>>()?
         };
 
-        nested_combine(config_stack)
+        Ok(nested_combine(config_stack))
     }
 
-    pub fn load_config_at_path(&self, path: impl AsRef<Path>) -> AHashMap<String, Value> {
+    pub fn load_config_at_path(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<AHashMap<String, Value>, SQLFluffUserError> {
         let path = path.as_ref();
 
+        // Files later in the list take precedence, following sqlfluff.
         let filename_options = [
-            /* "setup.cfg", "tox.ini", "pep8.ini", */
+            "setup.cfg",
+            "tox.ini",
+            "pep8.ini",
             ".sqlfluff",
-            ".sqruff", /* "pyproject.toml" */
+            ".sqruff",
+            "pyproject.toml",
         ];
 
         let mut configs = AHashMap::new();
@@ -358,45 +532,107 @@ impl ConfigLoader {
             for fname in filename_options {
                 let path = path.join(fname);
                 if path.exists() {
-                    self.load_config_file(path, &mut configs);
+                    self.load_config_file(path, &mut configs)?;
                 }
             }
         } else if path.is_file() {
-            self.load_config_file(path, &mut configs);
+            self.load_config_file(path, &mut configs)?;
         };
 
-        configs
+        Ok(configs)
     }
 
-    pub fn from_source(&self, source: &str) -> AHashMap<String, Value> {
+    pub fn from_source(&self, source: &str) -> Result<AHashMap<String, Value>, SQLFluffUserError> {
         let mut configs = AHashMap::new();
-        let elems = self.get_config_elems_from_file(None, Some(source));
+        let elems = self.get_config_elems_from_file(None, Some(source))?;
         self.incorporate_vals(&mut configs, elems);
-        configs
+        Ok(configs)
     }
 
-    pub fn load_config_file(&self, path: impl AsRef<Path>, configs: &mut AHashMap<String, Value>) {
-        let elems = self.get_config_elems_from_file(path.as_ref().into(), None);
+    pub fn load_config_file(
+        &self,
+        path: impl AsRef<Path>,
+        configs: &mut AHashMap<String, Value>,
+    ) -> Result<(), SQLFluffUserError> {
+        let path = path.as_ref();
+        let elems = if path
+            .extension()
+            .is_some_and(|extension| extension == "toml")
+        {
+            self.get_config_elems_from_toml(path)?
+        } else {
+            self.get_config_elems_from_file(path.into(), None)?
+        };
         self.incorporate_vals(configs, elems);
+        Ok(())
+    }
+
+    /// Read the `[tool.sqruff]` or `[tool.sqlfluff]` table of a TOML file.
+    fn get_config_elems_from_toml(
+        &self,
+        path: &Path,
+    ) -> Result<Vec<(Vec<String>, Value)>, SQLFluffUserError> {
+        let content =
+            std::fs::read_to_string(path).map_err(|error| config_file_error(Some(path), error))?;
+        let document = content
+            .parse::<toml::Table>()
+            .map_err(|error| config_file_error(Some(path), error))?;
+
+        let Some(tool) = document
+            .get("tool")
+            .and_then(toml::Value::as_table)
+            .and_then(|tool| tool.get("sqruff").or_else(|| tool.get("sqlfluff")))
+            .and_then(toml::Value::as_table)
+        else {
+            return Ok(Vec::new());
+        };
+
+        let mut buff = Vec::new();
+        walk_toml(tool, &mut Vec::new(), &mut buff);
+
+        for (key, value) in &mut buff {
+            // Values directly in the tool table belong to the core section.
+            if key.len() == 1 {
+                key.insert(0, "core".to_owned());
+            }
+
+            // Rule sections are addressed by a dotted name, such as
+            // `capitalisation.keywords`, which TOML splits into nested tables.
+            if key[0] == "rules" && key.len() > 3 {
+                let name = key.pop().unwrap();
+                let section = key.drain(1..).join(".");
+                key.extend([section, name]);
+            }
+
+            resolve_config_paths(key.last().unwrap(), value, path.parent());
+        }
+
+        Ok(buff)
     }
 
     fn get_config_elems_from_file(
         &self,
         path: Option<&Path>,
         config_string: Option<&str>,
-    ) -> Vec<(Vec<String>, Value)> {
+    ) -> Result<Vec<(Vec<String>, Value)>, SQLFluffUserError> {
         let mut buff = Vec::new();
-        let mut config = Ini::new();
+        // Keys keep their case, as templater context variables may need it,
+        // and values may continue over indented lines.
+        let mut config = Ini::new_cs();
+        config.set_multiline(true);
 
         let content = match (path, config_string) {
             (None, None) | (Some(_), Some(_)) => {
                 unimplemented!("One of fpath or config_string is required.")
             }
             (None, Some(text)) => text.to_owned(),
-            (Some(path), None) => std::fs::read_to_string(path).unwrap(),
+            (Some(path), None) => std::fs::read_to_string(path)
+                .map_err(|error| config_file_error(Some(path), error))?,
         };
 
-        config.read(content).unwrap();
+        config
+            .read(content)
+            .map_err(|error| config_file_error(path, error))?;
 
         for section in config.sections() {
             let key = if section == "sqlfluff" || section == "sqruff" {
@@ -405,7 +641,7 @@ impl ConfigLoader {
                 .strip_prefix("sqlfluff:")
                 .or_else(|| section.strip_prefix("sqruff:"))
             {
-                key.split(':').map(ToOwned::to_owned).collect()
+                key.split(':').map(str::to_lowercase).collect()
             } else {
                 continue;
             };
@@ -413,15 +649,10 @@ impl ConfigLoader {
             let config_map = config.get_map_ref();
             if let Some(section) = config_map.get(&section) {
                 for (name, value) in section {
-                    let value: Value = value.as_ref().unwrap().parse().unwrap();
-                    let name_lowercase = name.to_lowercase();
-
-                    if name_lowercase == "load_macros_from_path" {
-                        unimplemented!()
-                    } else if name_lowercase.ends_with("_path") || name_lowercase.ends_with("_dir")
-                    {
-                        unimplemented!()
-                    }
+                    let mut value = value
+                        .as_deref()
+                        .map_or(Value::None, |value| value.parse().unwrap());
+                    resolve_config_paths(name, &mut value, path.and_then(Path::parent));
 
                     let mut key = key.clone();
                     key.push(name.clone());
@@ -430,7 +661,7 @@ impl ConfigLoader {
             }
         }
 
-        buff
+        Ok(buff)
     }
 
     fn incorporate_vals(
@@ -457,7 +688,7 @@ impl ConfigLoader {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Default, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, Default, serde::Deserialize, serde::Serialize)]
 #[serde(untagged)]
 pub enum Value {
     Int(i32),
@@ -563,6 +794,32 @@ impl Value {
     }
 }
 
+impl Value {
+    fn from_toml(value: &toml::Value) -> Value {
+        match value {
+            toml::Value::String(value) => value.parse().unwrap(),
+            toml::Value::Integer(value) => i32::try_from(*value)
+                .map(Value::Int)
+                .unwrap_or_else(|_| Value::String(value.to_string().into())),
+            toml::Value::Float(value) => Value::Float(*value),
+            toml::Value::Boolean(value) => Value::Bool(*value),
+            toml::Value::Datetime(value) => Value::String(value.to_string().into()),
+            // Lists are comma separated strings in the INI config files.
+            toml::Value::Array(values) => Value::String(
+                values
+                    .iter()
+                    .map(|value| match value {
+                        toml::Value::String(value) => value.clone(),
+                        value => value.to_string(),
+                    })
+                    .join(",")
+                    .into(),
+            ),
+            toml::Value::Table(_) => unreachable!("tables are walked by `walk_toml`"),
+        }
+    }
+}
+
 impl FromStr for Value {
     type Err = ();
 
@@ -593,17 +850,92 @@ impl FromStr for Value {
     }
 }
 
+/// An error reading or parsing a config file, or a config given as a string.
+fn config_file_error(path: Option<&Path>, error: impl std::fmt::Display) -> SQLFluffUserError {
+    match path {
+        Some(path) => SQLFluffUserError::new(format!(
+            "Error loading config file {}: {error}",
+            path.display()
+        )),
+        None => SQLFluffUserError::new(format!("Error loading config: {error}")),
+    }
+}
+
+/// Paths are relative to the config file which declares them.
+fn resolve_config_paths(name: &str, value: &mut Value, config_dir: Option<&Path>) {
+    let name = name.to_lowercase();
+    if !name.ends_with("_path") && !name.ends_with("_dir") {
+        return;
+    }
+
+    if let (Some(config_dir), Some(paths)) = (config_dir, value.as_string()) {
+        let resolved = paths
+            .split(',')
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .map(|path| config_dir.join(path).display().to_string())
+            .join(",");
+        *value = Value::String(resolved.into());
+    }
+}
+
+/// Flatten a TOML table into config paths and values. Section names are
+/// lowercased and keys keep their case, to match the INI config files.
+fn walk_toml(table: &toml::Table, key: &mut Vec<String>, buff: &mut Vec<(Vec<String>, Value)>) {
+    for (name, value) in table {
+        match value {
+            toml::Value::Table(table) => {
+                key.push(name.to_lowercase());
+                walk_toml(table, key, buff);
+            }
+            value => {
+                key.push(name.clone());
+                buff.push((key.clone(), Value::from_toml(value)));
+            }
+        }
+        key.pop();
+    }
+}
+
 fn nested_combine(config_stack: Vec<AHashMap<String, Value>>) -> AHashMap<String, Value> {
-    let capacity = config_stack.len();
-    let mut result = AHashMap::with_capacity(capacity);
+    config_stack.into_iter().fold(AHashMap::new(), combine)
+}
 
-    for dict in config_stack {
-        for (key, value) in dict {
-            result.insert(key, value);
+/// Combine two configs, with values in `b` taking precedence over those in `a`.
+fn combine(mut a: AHashMap<String, Value>, b: AHashMap<String, Value>) -> AHashMap<String, Value> {
+    for (key, value_b) in b {
+        match (a.remove(&key), value_b) {
+            (Some(Value::Map(map_a)), Value::Map(map_b)) => {
+                a.insert(key, Value::Map(combine(map_a, map_b)));
+            }
+            (_, value) => {
+                a.insert(key, value);
+            }
         }
     }
+    a
+}
+
+fn parse_overrides(overrides: AHashMap<String, String>) -> AHashMap<String, Value> {
+    overrides
+        .into_iter()
+        .map(|(key, value)| (key, value.parse().unwrap()))
+        .collect()
+}
 
-    result
+fn apply_overrides(
+    mut configs: AHashMap<String, Value>,
+    overrides: &AHashMap<String, Value>,
+) -> AHashMap<String, Value> {
+    if !overrides.is_empty() {
+        configs
+            .entry("core".into())
+            .or_insert_with(|| Value::Map(AHashMap::new()))
+            .as_map_mut()
+            .unwrap()
+            .extend(overrides.clone());
+    }
+    configs
 }
 
 impl<'a> From<&'a FluffConfig> for Parser<'a> {
@@ -617,3 +949,153 @@ impl<'a> From<&'a FluffConfig> for Parser<'a> {
         Self::new(dialect, indentation_config)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ahash::AHashMap;
+
+    use super::{ConfigLoader, FluffConfig, Value};
+
+    #[test]
+    fn test_config_load_pyproject() {
+        let configs = ConfigLoader
+            .load_config_at_path("test/fixtures/config/pyproject")
+            .unwrap();
+        let config = FluffConfig::new(configs, None, None);
+
+        // `pyproject.toml` takes precedence over `setup.cfg`.
+        assert_eq!(config.get("dialect", "core").as_string(), Some("postgres"));
+        assert_eq!(config.get("max_line_length", "core"), &Value::Int(100));
+        assert_eq!(
+            config.get("exclude_rules", "core").as_string(),
+            Some("AM01,AM02")
+        );
+        assert_eq!(config.get("tab_space_size", "indentation"), &Value::Int(2));
+        assert_eq!(
+            config.raw["rules"]["capitalisation.keywords"]["capitalisation_policy"].as_string(),
+            Some("upper")
+        );
+        assert_eq!(
+            config.raw["rules"]["lt05"]["max_line_length"],
+            Value::Int(120)
+        );
+    }
+
+    #[test]
+    fn test_config_overrides() {
+        let config = FluffConfig::from_source("[sqruff]\ndialect = ansi\nrules = LT01\n")
+            .with_overrides(AHashMap::from_iter([
+                ("dialect".to_owned(), "bigquery".to_owned()),
+                ("rules".to_owned(), "CP01,LT02".to_owned()),
+                ("max_line_length".to_owned(), "120".to_owned()),
+            ]));
+
+        assert_eq!(config.get("dialect", "core").as_string(), Some("bigquery"));
+        assert_eq!(
+            config.get("rule_allowlist", "core"),
+            &Value::Array(vec![
+                Value::String("CP01".into()),
+                Value::String("LT02".into())
+            ])
+        );
+        assert_eq!(config.get("max_line_length", "core"), &Value::Int(120));
+
+        // Config files for a path don't override the command line.
+        let config = config
+            .make_child_from_path("test/fixtures/config/pyproject")
+            .unwrap();
+        assert_eq!(config.get("dialect", "core").as_string(), Some("bigquery"));
+        assert_eq!(config.get("max_line_length", "core"), &Value::Int(120));
+        assert_eq!(
+            config.get("exclude_rules", "core").as_string(),
+            Some("AM01,AM02")
+        );
+    }
+
+    #[test]
+    fn test_config_load_errors() {
+        assert!(ConfigLoader
+            .from_source("[sqruff\ndialect = ansi\n")
+            .is_err());
+        assert!(ConfigLoader
+            .load_config_file("test/fixtures/config/missing/.sqruff", &mut AHashMap::new())
+            .is_err());
+    }
+
+    #[test]
+    fn test_config_multiline_and_case() {
+        let config = FluffConfig::from_source(
+            "[sqruff]\nrules = LT01,\n    CP01\n\n[sqruff:templater:jinja:context]\nmyVar = 1\n",
+        );
+
+        assert_eq!(
+            config.get("rule_allowlist", "core"),
+            &Value::Array(vec![
+                Value::String("LT01".into()),
+                Value::String("CP01".into())
+            ])
+        );
+        assert_eq!(
+            config.raw["templater"]["jinja"]["context"]["myVar"],
+            Value::Int(1)
+        );
+    }
+
+    #[test]
+    fn test_config_inline_keeps_case() {
+        let mut config = FluffConfig::from_source(
+            "[sqruff:templater:jinja:context]\nmyVar = 1\n\n\
+             [sqruff:rules:capitalisation.keywords]\ncapitalisation_policy = lower\n",
+        );
+
+        config
+            .process_inline_config("-- sqruff:Templater:jinja:context:myVar:2")
+            .unwrap();
+        config
+            .process_inline_config(
+                "-- sqruff:rules:capitalisation.keywords:capitalisation_policy:upper",
+            )
+            .unwrap();
+
+        let context = config.raw["templater"]["jinja"]["context"]
+            .as_map()
+            .unwrap();
+        assert_eq!(context.len(), 1);
+        assert_eq!(context["myVar"], Value::Int(2));
+        assert_eq!(
+            config.raw["rules"]["capitalisation.keywords"]["capitalisation_policy"].as_string(),
+            Some("upper")
+        );
+    }
+
+    #[test]
+    fn test_config_explicit() {
+        let config = FluffConfig::from_source("[sqruff]\ndialect = ansi\n").explicit();
+
+        // Config files for a path don't apply when a config was given explicitly.
+        let child = config
+            .make_child_from_path("test/fixtures/config/pyproject")
+            .unwrap();
+        assert_eq!(child, config);
+    }
+
+    #[test]
+    fn test_config_nested_precedence() {
+        // Config files closer to the path take precedence over their parents.
+        let configs = ConfigLoader
+            .load_config_up_to_path("test/fixtures/config/nested/upper", None, false)
+            .unwrap();
+        assert_eq!(
+            configs["rules"]["capitalisation.keywords"]["capitalisation_policy"].as_string(),
+            Some("upper")
+        );
+
+        let config = FluffConfig::default()
+            .make_child_from_path("test/fixtures/config/nested/upper")
+            .unwrap();
+        assert_eq!(
+            config.raw["rules"]["capitalisation.keywords"]["capitalisation_policy"].as_string(),
+            Some("upper")
+        );
+    }
+}
@@ -1,3 +1,6 @@
+pub mod baseline;
+pub mod cache;
+pub mod changed_lines;
 pub mod common;
 pub mod core;
 pub mod linted_dir;
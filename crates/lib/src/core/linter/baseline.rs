@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+use std::hash::Hasher;
+
+use ahash::AHashMap;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use sqruff_lib_core::errors::{SQLBaseError, SQLFluffUserError};
+
+use super::common::{portable_path, StableHasher};
+use super::linted_file::LintedFile;
+
+const BASELINE_VERSION: u32 = 1;
+
+/// Violations which were already present when the baseline was recorded, and
+/// so aren't reported.
+///
+/// Violations are keyed by path, rule code and a hash of the content of the
+/// offending line, so they still match after unrelated lines are added or
+/// removed. Each key records how often it occurred, so further violations of
+/// the same kind on identical lines are still reported.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Baseline {
+    version: u32,
+    files: BTreeMap<String, Vec<BaselineEntry>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct BaselineEntry {
+    code: String,
+    hash: String,
+    count: usize,
+}
+
+impl Baseline {
+    /// Record the violations of linted files.
+    pub fn from_linted_files<'a>(files: impl IntoIterator<Item = &'a LintedFile>) -> Self {
+        let mut baseline = BTreeMap::new();
+
+        for file in files {
+            let lines = file.templated_file.source_str.lines().collect_vec();
+            let mut counts = BTreeMap::new();
+            for violation in file.get_violations(None) {
+                *counts.entry(key(&lines, &violation)).or_default() += 1;
+            }
+
+            if !counts.is_empty() {
+                let entries = counts
+                    .into_iter()
+                    .map(|((code, hash), count)| BaselineEntry { code, hash, count })
+                    .collect();
+                baseline.insert(portable_path(&file.path), entries);
+            }
+        }
+
+        Self {
+            version: BASELINE_VERSION,
+            files: baseline,
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, SQLFluffUserError> {
+        let baseline: Self = serde_json::from_str(json)
+            .map_err(|error| SQLFluffUserError::new(format!("Invalid baseline: {error}")))?;
+
+        if baseline.version != BASELINE_VERSION {
+            return Err(SQLFluffUserError::new(format!(
+                "Unsupported baseline version {}, expected {BASELINE_VERSION}",
+                baseline.version
+            )));
+        }
+
+        Ok(baseline)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// The number of violations in the baseline.
+    pub fn len(&self) -> usize {
+        self.files.values().flatten().map(|entry| entry.count).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Remove the violations of a file which are part of the baseline.
+    pub fn filter(
+        &self,
+        path: &str,
+        source: &str,
+        violations: Vec<SQLBaseError>,
+    ) -> Vec<SQLBaseError> {
+        let Some(entries) = self.files.get(&portable_path(path)) else {
+            return violations;
+        };
+
+        let lines = source.lines().collect_vec();
+        let mut remaining: AHashMap<(String, String), usize> = entries
+            .iter()
+            .map(|entry| ((entry.code.clone(), entry.hash.clone()), entry.count))
+            .collect();
+
+        violations
+            .into_iter()
+            .filter(
+                |violation| match remaining.get_mut(&key(&lines, violation)) {
+                    Some(count) if *count > 0 => {
+                        *count -= 1;
+                        false
+                    }
+                    _ => true,
+                },
+            )
+            .collect()
+    }
+}
+
+/// The rule code and line hash of a violation, given the lines of its file.
+fn key(lines: &[&str], violation: &SQLBaseError) -> (String, String) {
+    let line = violation
+        .line_no
+        .checked_sub(1)
+        .and_then(|index| lines.get(index))
+        .copied()
+        .unwrap_or_default();
+
+    let mut hasher = StableHasher::default();
+    hasher.write(line.trim().as_bytes());
+
+    (
+        violation.rule_code().to_owned(),
+        format!("{:016x}", hasher.finish()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use sqruff_lib_core::errors::{ErrorStructRule, SQLBaseError};
+
+    use super::{portable_path, Baseline, BaselineEntry, BASELINE_VERSION};
+
+    fn violation(line_no: usize) -> SQLBaseError {
+        SQLBaseError {
+            line_no,
+            rule: Some(ErrorStructRule {
+                name: "layout.spacing",
+                code: "LT01",
+            }),
+            ..SQLBaseError::default()
+        }
+    }
+
+    #[test]
+    fn test_baseline_filter() {
+        let (code, hash) = super::key(&["SELECT   1"], &violation(1));
+        let baseline = Baseline {
+            version: BASELINE_VERSION,
+            files: [(
+                "models/a.sql".to_owned(),
+                vec![BaselineEntry {
+                    code,
+                    hash,
+                    count: 1,
+                }],
+            )]
+            .into(),
+        };
+
+        // The known violation moved down a line, and a new one was added on an
+        // identical line.
+        let after = "-- header\nSELECT   1\nSELECT   1\n";
+        let remaining = baseline.filter("./models/a.sql", after, vec![violation(2), violation(3)]);
+        assert_eq!(remaining, [violation(3)]);
+
+        let json = baseline.to_json();
+        assert_eq!(Baseline::from_json(&json).unwrap(), baseline);
+    }
+
+    #[test]
+    fn test_portable_path() {
+        assert_eq!(portable_path("./models/a.sql"), "models/a.sql");
+        assert_eq!(portable_path("models/a.sql"), "models/a.sql");
+
+        let absolute = std::env::current_dir().unwrap().join("models/a.sql");
+        assert_eq!(portable_path(absolute.to_str().unwrap()), "models/a.sql");
+
+        let outside = std::env::temp_dir().join("models/a.sql");
+        let outside = outside.to_str().unwrap();
+        assert_eq!(portable_path(outside), outside.replace('\\', "/"));
+    }
+}
@@ -0,0 +1,311 @@
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use ahash::AHashMap;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use sqruff_lib_core::errors::{ErrorStructRule, SQLBaseError, SourceEdit};
+
+use super::common::{RenderedFile, StableHasher};
+use crate::core::config::Value;
+use crate::core::rules::base::ErasedRule;
+use crate::rules::rules;
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The file marking a directory as created by the cache, so it can be removed
+/// once it belongs to another sqruff version.
+const MARKER: &str = "sqruff-cache";
+
+/// Entries which haven't been used for this long are removed when the cache is
+/// opened.
+const MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Config values which don't change the violations of a file.
+const IGNORED_CONFIG: &[&str] = &["processes"];
+
+/// Caches the violations of linted files on disk.
+///
+/// Entries are keyed by a hash of everything the violations depend on: the
+/// path, contents and templated SQL of the file, its config, the enabled rules
+/// and the sqruff version. Results of other sqruff versions, and entries which
+/// haven't been used for a while, are removed when the cache is opened.
+#[derive(Debug)]
+pub struct LintCache {
+    dir: PathBuf,
+    /// The names of the rules by code, to restore the rules of violations.
+    rule_names: AHashMap<&'static str, &'static str>,
+}
+
+impl LintCache {
+    pub fn new(root: &Path) -> std::io::Result<Self> {
+        let dir = root.join(VERSION);
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join(MARKER), "")?;
+        // Only ignore the directory the cache owns, as `cache_dir` may be shared.
+        let gitignore = dir.join(".gitignore");
+        if !gitignore.exists() {
+            fs::write(gitignore, "# Automatically created by sqruff.\n*\n")?;
+        }
+
+        // Only directories of other versions created by the cache are removed,
+        // in case `cache_dir` points somewhere with other contents.
+        for entry in fs::read_dir(root)?.flatten() {
+            let path = entry.path();
+            if path != dir
+                && entry.file_type().is_ok_and(|it| it.is_dir())
+                && entry.file_name().to_str().is_some_and(is_version)
+                && path.join(MARKER).is_file()
+            {
+                _ = fs::remove_dir_all(path);
+            }
+        }
+
+        let now = SystemTime::now();
+        for entry in fs::read_dir(&dir)?.flatten() {
+            let path = entry.path();
+            let expired = entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|modified| now.duration_since(modified).unwrap_or_default() > MAX_AGE);
+            if expired && path.extension().is_some_and(|it| it == "json") {
+                _ = fs::remove_file(path);
+            }
+        }
+
+        let rule_names = rules()
+            .into_iter()
+            .map(|rule| (rule.code(), rule.name()))
+            .chain([
+                ("NOQA", "noqa"),
+                ("TMP", "templating"),
+                ("CFG", "inline_config"),
+            ])
+            .collect();
+
+        Ok(Self { dir, rule_names })
+    }
+
+    pub fn key(&self, rendered: &RenderedFile, rules: &[ErasedRule]) -> String {
+        let mut config = rendered.config.raw.clone();
+        if let Some(core) = config.get_mut("core").and_then(Value::as_map_mut) {
+            for key in IGNORED_CONFIG {
+                core.remove(*key);
+            }
+        }
+
+        let mut hasher = StableHasher::default();
+        VERSION.hash(&mut hasher);
+        rendered.filename.hash(&mut hasher);
+        rendered.source_str.hash(&mut hasher);
+        rendered.templated_file.templated().hash(&mut hasher);
+        for violation in &rendered.templater_violations {
+            violation.description.hash(&mut hasher);
+        }
+        hash_value(&Value::Map(config), &mut hasher);
+        for rule in rules {
+            rule.code().hash(&mut hasher);
+        }
+
+        format!("{:016x}", hasher.finish())
+    }
+
+    /// The violations cached for a key, if any.
+    pub fn get(&self, key: &str) -> Option<Vec<SQLBaseError>> {
+        let path = self.path(key);
+        let json = fs::read_to_string(&path).ok()?;
+        let violations: Vec<CachedViolation> = serde_json::from_str(&json).ok()?;
+
+        // Entries are expired by their modification time, so mark this one as used.
+        if let Ok(file) = fs::File::options().write(true).open(&path) {
+            _ = file.set_modified(SystemTime::now());
+        }
+
+        Some(
+            violations
+                .into_iter()
+                .map(|violation| violation.into_error(&self.rule_names))
+                .collect(),
+        )
+    }
+
+    pub fn put(&self, key: &str, violations: &[SQLBaseError]) {
+        let violations = violations.iter().map(CachedViolation::from).collect_vec();
+        // A failure to cache only costs time on the next run.
+        _ = fs::write(self.path(key), serde_json::to_string(&violations).unwrap());
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(key).with_extension("json")
+    }
+}
+
+/// Whether a directory name looks like a sqruff version, e.g. `0.22.0`.
+fn is_version(name: &str) -> bool {
+    let release = name.split_once('-').map_or(name, |(release, _)| release);
+    let parts = release.split('.').collect_vec();
+    parts.len() == 3
+        && parts
+            .iter()
+            .all(|part| !part.is_empty() && part.bytes().all(|byte| byte.is_ascii_digit()))
+}
+
+/// Hash a config value, visiting maps in the order of their keys.
+fn hash_value(value: &Value, hasher: &mut StableHasher) {
+    match value {
+        Value::Int(value) => {
+            hasher.write_u8(0);
+            value.hash(hasher);
+        }
+        Value::Bool(value) => {
+            hasher.write_u8(1);
+            value.hash(hasher);
+        }
+        Value::Float(value) => {
+            hasher.write_u8(2);
+            value.to_bits().hash(hasher);
+        }
+        Value::String(value) => {
+            hasher.write_u8(3);
+            value.hash(hasher);
+        }
+        Value::Map(map) => {
+            hasher.write_u8(4);
+            map.len().hash(hasher);
+            for (key, value) in map.iter().sorted_by_key(|(key, _)| *key) {
+                key.hash(hasher);
+                hash_value(value, hasher);
+            }
+        }
+        Value::Array(values) => {
+            hasher.write_u8(5);
+            values.len().hash(hasher);
+            for value in values {
+                hash_value(value, hasher);
+            }
+        }
+        Value::None => hasher.write_u8(6),
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedViolation {
+    code: Option<String>,
+    description: String,
+    line_no: usize,
+    line_pos: usize,
+    source_slice: (usize, usize),
+    fatal: bool,
+    ignore: bool,
+    warning: bool,
+    fixable: bool,
+    fixes: Vec<(usize, usize, String)>,
+}
+
+impl From<&SQLBaseError> for CachedViolation {
+    fn from(violation: &SQLBaseError) -> Self {
+        Self {
+            code: violation.rule.as_ref().map(|rule| rule.code.to_owned()),
+            description: violation.description.clone(),
+            line_no: violation.line_no,
+            line_pos: violation.line_pos,
+            source_slice: (violation.source_slice.start, violation.source_slice.end),
+            fatal: violation.fatal,
+            ignore: violation.ignore,
+            warning: violation.warning,
+            fixable: violation.fixable,
+            fixes: violation
+                .fixes
+                .iter()
+                .map(|fix| {
+                    (
+                        fix.source_slice.start,
+                        fix.source_slice.end,
+                        fix.edit.clone(),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+impl CachedViolation {
+    fn into_error(self, rule_names: &AHashMap<&'static str, &'static str>) -> SQLBaseError {
+        SQLBaseError {
+            fatal: self.fatal,
+            ignore: self.ignore,
+            warning: self.warning,
+            line_no: self.line_no,
+            line_pos: self.line_pos,
+            description: self.description,
+            rule: self.code.and_then(|code| {
+                rule_names
+                    .get_key_value(code.as_str())
+                    .map(|(&code, &name)| ErrorStructRule { name, code })
+            }),
+            source_slice: self.source_slice.0..self.source_slice.1,
+            fixable: self.fixable,
+            fixes: self
+                .fixes
+                .into_iter()
+                .map(|(start, end, edit)| SourceEdit {
+                    source_slice: start..end,
+                    edit,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LintCache;
+    use crate::core::config::FluffConfig;
+    use crate::core::linter::core::Linter;
+
+    #[test]
+    fn test_lint_cache() {
+        let root = tempfile::tempdir().unwrap();
+        let cache = LintCache::new(root.path()).unwrap();
+
+        let config = FluffConfig::from_source("[sqruff]\nrules = LT01\n");
+        let linter = Linter::new(config, None, None);
+        let rendered = linter
+            .render_string("SELECT   1\n", "a.sql".into(), linter.config())
+            .unwrap();
+        let key = cache.key(&rendered, linter.rules());
+        assert_eq!(cache.get(&key), None);
+
+        let violations = linter.lint_rendered(rendered.clone(), false).violations;
+        cache.put(&key, &violations);
+        assert_eq!(cache.get(&key), Some(violations));
+
+        let mut changed = rendered;
+        changed.source_str.push('\n');
+        assert_ne!(cache.key(&changed, linter.rules()), key);
+    }
+
+    #[test]
+    fn test_lint_cache_prunes_own_directories() {
+        let root = tempfile::tempdir().unwrap();
+        let old = root.path().join("0.1.0");
+        std::fs::create_dir(&old).unwrap();
+        std::fs::write(old.join(super::MARKER), "").unwrap();
+        let unrelated = root.path().join("0.2.0");
+        std::fs::create_dir(&unrelated).unwrap();
+        let models = root.path().join("models");
+        std::fs::create_dir(&models).unwrap();
+        std::fs::write(models.join(super::MARKER), "").unwrap();
+
+        LintCache::new(root.path()).unwrap();
+        assert!(!old.exists());
+        assert!(unrelated.exists());
+        assert!(models.exists());
+        let dir = root.path().join(super::VERSION);
+        assert!(dir.join(super::MARKER).exists());
+        assert!(dir.join(".gitignore").exists());
+        assert!(!root.path().join(".gitignore").exists());
+    }
+}
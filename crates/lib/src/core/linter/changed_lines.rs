@@ -0,0 +1,153 @@
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use ahash::AHashMap;
+use sqruff_lib_core::errors::SQLFluffUserError;
+
+/// The lines changed relative to a git ref, used to only report violations on
+/// those lines.
+#[derive(Debug, Default, Clone)]
+pub struct ChangedLines {
+    /// Changed line ranges, keyed by canonical file path.
+    files: AHashMap<PathBuf, Vec<RangeInclusive<usize>>>,
+}
+
+impl ChangedLines {
+    /// Find the lines changed in the working tree since the merge base of
+    /// `base` and `HEAD`, using `git diff`. Like `git diff`, this leaves out
+    /// untracked files, so new files are only linted once they are added.
+    pub fn from_git(base: &str) -> Result<Self, SQLFluffUserError> {
+        let root = git(&["rev-parse", "--show-toplevel"])?;
+        // The prefixes are set explicitly, as `diff.noprefix` and
+        // `diff.mnemonicPrefix` would change the file headers.
+        let diff = git(&[
+            "-c",
+            "core.quotePath=false",
+            "diff",
+            "-U0",
+            "--no-color",
+            "--no-ext-diff",
+            "--src-prefix=a/",
+            "--dst-prefix=b/",
+            "--merge-base",
+            base,
+        ])?;
+
+        Ok(Self::from_diff(Path::new(root.trim_end()), &diff))
+    }
+
+    /// Parse the output of `git diff -U0`, with paths relative to `root`.
+    pub fn from_diff(root: &Path, diff: &str) -> Self {
+        let mut files: AHashMap<PathBuf, Vec<RangeInclusive<usize>>> = AHashMap::new();
+        let mut current = None;
+        let mut previous = "";
+
+        for line in diff.lines() {
+            // An added line starting with `++ ` looks like a file header too.
+            let header = previous.starts_with("--- ");
+            previous = line;
+
+            if let Some(path) = line.strip_prefix("+++ ").filter(|_| header) {
+                current = path.strip_prefix("b/").map(|path| {
+                    let path = root.join(path);
+                    let path = path.canonicalize().unwrap_or(path);
+                    files.entry(path.clone()).or_default();
+                    path
+                });
+            } else if let Some(hunk) = line.strip_prefix("@@ ") {
+                let Some(path) = &current else {
+                    continue;
+                };
+                if let Some(lines) = parse_hunk(hunk) {
+                    files.get_mut(path).unwrap().push(lines);
+                }
+            }
+        }
+
+        Self { files }
+    }
+
+    /// Whether a file has any changes.
+    pub fn contains_file(&self, path: &Path) -> bool {
+        self.files.contains_key(&canonical(path))
+    }
+
+    /// The changed line ranges of a file, empty if it hasn't changed.
+    pub fn lines_of(&self, path: &Path) -> &[RangeInclusive<usize>] {
+        self.files.get(&canonical(path)).map_or(&[], Vec::as_slice)
+    }
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// The added lines of a hunk header like `-12,3 +14,2 @@`, or `None` if
+/// lines were only removed.
+fn parse_hunk(hunk: &str) -> Option<RangeInclusive<usize>> {
+    let added = hunk.split_whitespace().find(|it| it.starts_with('+'))?;
+    let (start, count) = match added[1..].split_once(',') {
+        Some((start, count)) => (start.parse().ok()?, count.parse().ok()?),
+        None => (added[1..].parse().ok()?, 1),
+    };
+
+    (count > 0).then(|| start..=start + count - 1)
+}
+
+fn git(args: &[&str]) -> Result<String, SQLFluffUserError> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .map_err(|error| SQLFluffUserError::new(format!("Failed to run git: {error}")))?;
+
+    if !output.status.success() {
+        return Err(SQLFluffUserError::new(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::ChangedLines;
+
+    #[test]
+    fn test_changed_lines_from_diff() {
+        let diff = "\
+diff --git a/models/a.sql b/models/a.sql
+index 1111111..2222222 100644
+--- a/models/a.sql
++++ b/models/a.sql
+@@ -1,0 +2,3 @@ select
++a,
++b,
++c
+@@ -10 +12 @@ from t
+-where x
++where y
+@@ -20,2 +21,0 @@
+-limit 1
+-;
+diff --git a/models/b.sql b/models/b.sql
+deleted file mode 100644
+--- a/models/b.sql
++++ /dev/null
+@@ -1 +0,0 @@
+-select 1
+";
+        let root = Path::new("/repo");
+        let changed = ChangedLines::from_diff(root, diff);
+
+        let a = root.join("models/a.sql");
+        assert!(changed.contains_file(&a));
+        assert_eq!(changed.lines_of(&a), [2..=4, 12..=12]);
+        assert!(!changed.contains_file(&root.join("models/b.sql")));
+    }
+}
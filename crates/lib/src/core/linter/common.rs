@@ -1,7 +1,12 @@
-use sqruff_lib_core::errors::{SQLBaseError, SQLTemplaterError};
+use std::hash::Hasher;
+use std::path::{Component, Path};
+
+use sqruff_lib_core::errors::SQLBaseError;
 use sqruff_lib_core::parser::segments::base::ErasedSegment;
 use sqruff_lib_core::templaters::base::TemplatedFile;
 
+use crate::core::config::FluffConfig;
+
 /// An object to store the result of a templated file/string.
 ///
 /// This is notable as it's the intermediate state between what happens
@@ -9,7 +14,10 @@ use sqruff_lib_core::templaters::base::TemplatedFile;
 #[derive(Debug, Clone)]
 pub struct RenderedFile {
     pub templated_file: TemplatedFile,
-    pub templater_violations: Vec<SQLTemplaterError>,
+    /// Violations found while rendering, like a file which can't be templated.
+    pub templater_violations: Vec<SQLBaseError>,
+    /// The config for this file, including any inline config commands.
+    pub config: FluffConfig,
     pub(crate) filename: String,
     pub source_str: String,
 }
@@ -20,6 +28,61 @@ pub struct ParsedString {
     pub tree: Option<ErasedSegment>,
     pub violations: Vec<SQLBaseError>,
     pub templated_file: TemplatedFile,
+    pub config: FluffConfig,
     pub filename: String,
     pub source_str: String,
 }
+
+/// A 64-bit FNV-1a hasher. Unlike the standard library's hasher, its hashes
+/// don't change between builds, so they can be stored on disk.
+pub(crate) struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+/// A path relative to the working directory with `/` separators, so that it
+/// is the same across checkouts and platforms. Paths outside the working
+/// directory stay absolute.
+pub(crate) fn portable_path(path: &str) -> String {
+    let path = Path::new(path);
+    let relative = std::env::current_dir().ok().and_then(|cwd| {
+        path.strip_prefix(&cwd)
+            .ok()
+            .map(Path::to_path_buf)
+            .or_else(|| {
+                let path = path.canonicalize().ok()?;
+                let cwd = cwd.canonicalize().ok()?;
+                path.strip_prefix(cwd).ok().map(Path::to_path_buf)
+            })
+    });
+
+    let mut portable = String::new();
+    let mut parts = Vec::new();
+    for component in relative.as_deref().unwrap_or(path).components() {
+        match component {
+            Component::Prefix(prefix) => portable.push_str(&prefix.as_os_str().to_string_lossy()),
+            Component::RootDir => portable.push('/'),
+            Component::CurDir => {}
+            Component::ParentDir | Component::Normal(_) => {
+                parts.push(component.as_os_str().to_string_lossy());
+            }
+        }
+    }
+    portable.push_str(&parts.join("/"));
+    portable
+}
@@ -1,16 +1,19 @@
 use std::borrow::Cow;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, OnceLock};
+use std::sync::{Arc, Mutex, OnceLock};
 
 use ahash::{AHashMap, AHashSet};
 use itertools::Itertools;
 use rayon::iter::{IntoParallelRefIterator as _, ParallelIterator as _};
 use smol_str::{SmolStr, ToSmolStr};
 use sqruff_lib_core::dialects::base::Dialect;
+use sqruff_lib_core::dialects::syntax::{SyntaxKind, SyntaxSet};
 use sqruff_lib_core::errors::{
-    SQLBaseError, SQLFluffUserError, SQLLexError, SQLLintError, SQLParseError, SqlError,
+    ErrorStructRule, SQLBaseError, SQLFluffSkipFile, SQLFluffUserError, SQLLexError, SQLLintError,
+    SQLParseError, SqlError,
 };
 use sqruff_lib_core::helpers;
 use sqruff_lib_core::linter::compute_anchor_edit_info;
@@ -21,6 +24,9 @@ use sqruff_lib_core::parser::segments::fix::SourceFix;
 use sqruff_lib_core::templaters::base::TemplatedFile;
 use walkdir::WalkDir;
 
+use super::baseline::Baseline;
+use super::cache::LintCache;
+use super::changed_lines::ChangedLines;
 use super::linted_dir::LintedDir;
 use crate::cli::formatters::Formatter;
 use crate::core::config::FluffConfig;
@@ -38,6 +44,12 @@ pub struct Linter {
     formatter: Option<Arc<dyn Formatter>>,
     templater: &'static dyn Templater,
     rules: OnceLock<Vec<ErasedRule>>,
+    /// The rules of files linted with configs other than the linter's own,
+    /// which are shared by all the files of a directory with its own config.
+    rule_packs: Mutex<Vec<(FluffConfig, Vec<ErasedRule>)>>,
+    changed_lines: Option<ChangedLines>,
+    baseline: Option<Baseline>,
+    cache: Option<LintCache>,
 }
 
 impl Linter {
@@ -59,11 +71,31 @@ impl Linter {
                 }
             }
         };
+        let cache =
+            config.get("cache_dir", "core").as_string().and_then(|dir| {
+                match LintCache::new(Path::new(dir)) {
+                    Ok(cache) => Some(cache),
+                    Err(error) => {
+                        if let Some(formatter) = &formatter {
+                            formatter.dispatch_file_warning(
+                                dir,
+                                &format!("Not caching lint results, as it can't be used: {error}"),
+                            );
+                        }
+                        None
+                    }
+                }
+            });
+
         Linter {
             config,
             formatter,
             templater,
             rules: OnceLock::new(),
+            rule_packs: Mutex::new(Vec::new()),
+            changed_lines: None,
+            baseline: None,
+            cache,
         }
     }
 
@@ -96,8 +128,6 @@ impl Linter {
 
         let mut violations: Vec<Box<dyn SqlError>> = vec![];
 
-        // Scan the raw file for config commands.
-        self.config.process_raw_file_for_config(sql);
         let rendered = self.render_string(sql, f_name.clone(), &self.config)?;
 
         for violation in &rendered.templater_violations {
@@ -157,35 +187,150 @@ impl Linter {
             }
         }
 
-        expanded_paths
-            .par_iter()
-            .filter(|path| !ignorer(Path::new(path)))
-            .map(|path| {
-                let rendered = self.render_file(path.clone());
-                self.lint_rendered(rendered, fix)
-            })
-            .for_each(|linted_file| {
-                let path = expanded_path_to_linted_dir[&linted_file.path];
-                result.paths[path].add(linted_file);
-            });
+        // Resolve the config once per directory, so that config files in
+        // subdirectories apply to the files underneath them.
+        let mut directory_configs = AHashMap::new();
+        for path in &expanded_paths {
+            let directory = Path::new(path).parent().map(Path::to_path_buf);
+            directory_configs
+                .entry(directory)
+                .or_insert_with(|| self.config.make_child_from_path(path));
+        }
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(self.processes())
+            .build()
+            .unwrap();
+
+        pool.install(|| {
+            expanded_paths
+                .par_iter()
+                .filter(|path| !ignorer(Path::new(path)))
+                .filter(|path| {
+                    self.changed_lines.as_ref().map_or(true, |changed_lines| {
+                        changed_lines.contains_file(Path::new(path))
+                    })
+                })
+                .filter_map(|path| {
+                    let config = directory_configs
+                        [&Path::new(path).parent().map(Path::to_path_buf)]
+                        .as_ref()
+                        .map_err(|error| SQLFluffSkipFile::new(error.value.clone()));
+                    match config.and_then(|config| self.render_file(path.clone(), config)) {
+                        Ok(rendered) => Some(self.lint_rendered(rendered, fix)),
+                        Err(skip) => {
+                            if let Some(formatter) = &self.formatter {
+                                formatter.dispatch_file_skipped(path, &skip);
+                            }
+                            None
+                        }
+                    }
+                })
+                .for_each(|linted_file| {
+                    let path = expanded_path_to_linted_dir[&linted_file.path];
+                    result.paths[path].add(linted_file);
+                });
+        });
 
         result
     }
 
+    /// The number of threads to lint with. Zero or negative values of the
+    /// `processes` config are subtracted from the number of CPUs.
+    fn processes(&self) -> usize {
+        let processes = self
+            .config
+            .get("processes", "core")
+            .as_int()
+            .unwrap_or_default();
+
+        if processes > 0 {
+            processes as usize
+        } else {
+            let cpus = std::thread::available_parallelism().map_or(1, NonZeroUsize::get);
+            cpus.saturating_sub(processes.unsigned_abs() as usize)
+                .max(1)
+        }
+    }
+
     pub fn get_rulepack(&self) -> RulePack {
         let rs = get_ruleset();
         rs.get_rulepack(&self.config)
     }
 
-    pub fn render_file(&self, fname: String) -> RenderedFile {
-        let in_str = std::fs::read_to_string(&fname).unwrap();
-        self.render_string(&in_str, fname, &self.config).unwrap()
+    /// Render a file, skipping it if it is over the configured size limits.
+    pub fn render_file(
+        &self,
+        fname: String,
+        config: &FluffConfig,
+    ) -> Result<RenderedFile, SQLFluffSkipFile> {
+        // Check the size before reading the file, so huge files are never loaded.
+        let unreadable = |error: std::io::Error| {
+            SQLFluffSkipFile::new(format!("Unable to read file {fname:?}: {error}"))
+        };
+        let byte_limit = config
+            .get("large_file_skip_byte_limit", "core")
+            .as_int()
+            .unwrap_or_default();
+        if byte_limit > 0 {
+            let size = std::fs::metadata(&fname).map_err(unreadable)?.len();
+            if size > byte_limit as u64 {
+                return Err(SQLFluffSkipFile::new(format!(
+                    "Length of file {fname:?} is {size} bytes which is over the limit of \
+                     {byte_limit} bytes. Skipping to avoid parser lock. Users can increase this \
+                     limit in their config by setting the 'large_file_skip_byte_limit' value, or \
+                     disable by setting it to zero."
+                )));
+            }
+        }
+
+        let in_str = std::fs::read_to_string(&fname).map_err(unreadable)?;
+
+        let char_limit = config
+            .get("large_file_skip_char_limit", "core")
+            .as_int()
+            .unwrap_or_default();
+        if char_limit > 0 {
+            let length = in_str.chars().count();
+            if length > char_limit as usize {
+                return Err(SQLFluffSkipFile::new(format!(
+                    "Length of file {fname:?} is {length} characters which is over the limit of \
+                     {char_limit} characters. Skipping to avoid parser lock. Users can increase \
+                     this limit in their config by setting the 'large_file_skip_char_limit' \
+                     value, or disable by setting it to zero."
+                )));
+            }
+        }
+
+        self.render_string(&in_str, fname, config)
+            .map_err(|error| SQLFluffSkipFile::new(error.value))
     }
 
     pub fn lint_rendered(&self, rendered: RenderedFile, fix: bool) -> LintedFile {
+        // Only lint results are cached, fixing needs the parse tree.
+        let cache = self.cache.as_ref().filter(|_| !fix).map(|cache| {
+            let key = cache.key(&rendered, &self.rules_for(&rendered.config));
+            (cache, key)
+        });
+
+        if let Some(violations) = cache.as_ref().and_then(|(cache, key)| cache.get(key)) {
+            return self.report_linted_file(LintedFile {
+                path: rendered.filename,
+                patches: Vec::new(),
+                templated_file: rendered.templated_file,
+                violations,
+                ignore_mask: None,
+            });
+        }
+
         let tables = Tables::default();
         let parsed = self.parse_rendered(&tables, rendered);
-        self.lint_parsed(&tables, parsed, fix)
+        let linted_file = self.lint_parsed_unreported(&tables, parsed, fix);
+        if let Some((cache, key)) = &cache {
+            cache.put(key, &linted_file.violations);
+        }
+
+        self.report_linted_file(linted_file)
     }
 
     pub fn lint_parsed(
@@ -194,24 +339,100 @@ impl Linter {
         parsed_string: ParsedString,
         fix: bool,
     ) -> LintedFile {
+        let linted_file = self.lint_parsed_unreported(tables, parsed_string, fix);
+        self.report_linted_file(linted_file)
+    }
+
+    /// Lint a parsed file, without filtering its violations for the report.
+    fn lint_parsed_unreported(
+        &self,
+        tables: &Tables,
+        parsed_string: ParsedString,
+        fix: bool,
+    ) -> LintedFile {
+        // Errors of the categories listed in `ignore` aren't reported.
+        let ignore = parsed_string
+            .config
+            .get("ignore", "core")
+            .as_array()
+            .unwrap_or_default();
+        let ignores = |category: &str| ignore.iter().any(|it| it.as_string() == Some(category));
+
         let mut violations = parsed_string.violations;
+        violations.retain(|violation| match violation.rule_code() {
+            "TMP" => !ignores("templating"),
+            _ => violation.rule.is_some() || !ignores("parsing"),
+        });
+        let warn_unused_ignores = parsed_string
+            .config
+            .get("warn_unused_ignores", "core")
+            .as_bool()
+            .unwrap_or(false);
+
+        let (patches, ignore_mask, unused_violations) = match parsed_string.tree {
+            Some(erased_segment) => {
+                let (mut tree, ignore_mask, initial_linting_errors) = self.lint_fix_parsed(
+                    tables,
+                    erased_segment,
+                    &parsed_string.templated_file,
+                    &parsed_string.config,
+                    fix,
+                );
+                if !ignores("linting") {
+                    violations.extend(initial_linting_errors.into_iter().map_into());
+                }
 
-        let (patches, ignore_mask, initial_linting_errors) =
-            parsed_string
-                .tree
-                .map_or((Vec::new(), None, Vec::new()), |erased_segment| {
-                    let (tree, ignore_mask, initial_linting_errors) = self.lint_fix_parsed(
-                        tables,
-                        erased_segment,
-                        &parsed_string.templated_file,
-                        fix,
-                    );
-                    let patches = tree.iter_patches(&parsed_string.templated_file);
-                    (patches, ignore_mask, initial_linting_errors)
-                });
-        violations.extend(initial_linting_errors.into_iter().map_into());
+                let mut unused_violations = Vec::new();
+                if let Some(ignore_mask) = ignore_mask.as_ref().filter(|_| warn_unused_ignores) {
+                    // Directives are only unused if the rules they name ran.
+                    let rules = self.rules_for(&parsed_string.config);
+                    let ran_rules = if ignores("linting") {
+                        Default::default()
+                    } else {
+                        rules.iter().map(|rule| rule.code()).collect()
+                    };
+                    let available_rules = get_ruleset().register.keys().copied().collect();
+
+                    if fix {
+                        let fixes = ignore_mask.unused_fixes(
+                            &tree,
+                            &violations,
+                            &ran_rules,
+                            &available_rules,
+                        );
+                        if !fixes.is_empty() {
+                            let mut anchor_info = compute_anchor_edit_info(fixes.into_iter());
+                            (tree, _, _, _) = tree.apply_fixes(&mut anchor_info);
+                        }
+                    }
+
+                    unused_violations =
+                        ignore_mask.unused_violations(&violations, &ran_rules, &available_rules);
+                }
 
-        // Filter violations with ignore mask
+                let patches = tree.iter_patches(&parsed_string.templated_file);
+                (patches, ignore_mask, unused_violations)
+            }
+            None => (Vec::new(), None, Vec::new()),
+        };
+
+        // Violations of the rules listed in `warnings` are reported, but don't fail the run.
+        let warnings = parsed_string
+            .config
+            .get("warnings", "core")
+            .as_array()
+            .unwrap_or_default();
+        for violation in &mut violations {
+            if warnings
+                .iter()
+                .any(|code| code.as_string() == Some(violation.rule_code()))
+            {
+                violation.warning = true;
+            }
+        }
+
+        // Filter violations with ignore mask. Unused noqa warnings are added
+        // afterwards, as a directive would otherwise mask its own warning.
         let violations = violations
             .into_iter()
             .filter(|violation| {
@@ -219,16 +440,39 @@ impl Linter {
                     .as_ref()
                     .map_or(true, |ignore_mask| !ignore_mask.is_masked(violation))
             })
+            .chain(unused_violations)
             .collect();
 
-        // TODO Need to error out unused noqas
-        let linted_file = LintedFile {
+        LintedFile {
             path: parsed_string.filename,
             patches,
             templated_file: parsed_string.templated_file,
             violations,
             ignore_mask,
-        };
+        }
+    }
+
+    /// Drop the violations which aren't reported on this run, and pass the
+    /// file to the formatter.
+    fn report_linted_file(&self, mut linted_file: LintedFile) -> LintedFile {
+        // Only report violations on changed lines, when linting a diff.
+        if let Some(changed_lines) = &self.changed_lines {
+            let changed_lines = changed_lines.lines_of(Path::new(&linted_file.path));
+            linted_file.violations.retain(|violation| {
+                changed_lines
+                    .iter()
+                    .any(|lines| lines.contains(&violation.line_no))
+            });
+        }
+
+        // Violations recorded in the baseline are grandfathered in.
+        if let Some(baseline) = &self.baseline {
+            linted_file.violations = baseline.filter(
+                &linted_file.path,
+                &linted_file.templated_file.source_str,
+                std::mem::take(&mut linted_file.violations),
+            );
+        }
 
         if let Some(formatter) = &self.formatter {
             formatter.dispatch_file_violations(&linted_file, false);
@@ -242,8 +486,29 @@ impl Linter {
         tables: &Tables,
         mut tree: ErasedSegment,
         templated_file: &TemplatedFile,
+        config: &FluffConfig,
         fix: bool,
     ) -> (ErasedSegment, Option<IgnoreMask>, Vec<SQLLintError>) {
+        let rules = self.rules_for(config);
+
+        // Fixing a file which is already unparsable risks making it worse, so
+        // only do so when explicitly allowed.
+        let unparsable_count = Self::count_unparsable(&tree);
+        let fix_even_unparsable = config
+            .get("fix_even_unparsable", "core")
+            .as_bool()
+            .unwrap_or(false);
+        let fix = if fix && unparsable_count > 0 && !fix_even_unparsable {
+            self.dispatch_file_warning(
+                templated_file.name(),
+                "Not fixing, as the file contains unparsable sections. Set \
+                 `fix_even_unparsable = True` to fix it anyway.",
+            );
+            false
+        } else {
+            fix
+        };
+
         let mut tmp;
         let mut initial_linting_errors = Vec::new();
         let phases: &[_] = if fix {
@@ -256,11 +521,24 @@ impl Linter {
 
         // If we are fixing then we want to loop up to the runaway_limit, otherwise just
         // once for linting.
-        let loop_limit = if fix { 10 } else { 1 };
+        let loop_limit = if fix {
+            config
+                .get("runaway_limit", "core")
+                .as_int()
+                .map_or(10, |limit| limit.max(1) as usize)
+        } else {
+            1
+        };
+        // Rules whose fixes brought back an earlier version of the file, and rules which
+        // were still changing it when the loop limit was reached.
+        let mut looping_rules = AHashSet::new();
+        let mut runaway_rules = AHashSet::new();
+        // Whether fixes are checked not to make the file unparsable one rule at a time,
+        // rather than once per loop.
+        let mut check_each_fix = false;
         // Look for comment segments which might indicate lines to ignore.
         let (ignore_mask, violations): (Option<IgnoreMask>, Vec<SQLBaseError>) = {
-            let disable_noqa = self
-                .config
+            let disable_noqa = config
                 .get("disable_noqa", "core")
                 .as_bool()
                 .unwrap_or(false);
@@ -275,8 +553,7 @@ impl Linter {
 
         for phase in phases {
             let mut rules_this_phase = if phases.len() > 1 {
-                tmp = self
-                    .rules()
+                tmp = rules
                     .iter()
                     .filter(|rule| rule.lint_phase() == *phase)
                     .cloned()
@@ -284,7 +561,7 @@ impl Linter {
 
                 &tmp
             } else {
-                self.rules()
+                &rules[..]
             };
 
             for loop_ in 0..(if *phase == LintPhase::Main {
@@ -293,80 +570,203 @@ impl Linter {
                 2
             }) {
                 let is_first_linter_pass = *phase == phases[0] && loop_ == 0;
-                let mut changed = false;
-
                 if is_first_linter_pass {
-                    rules_this_phase = self.rules();
+                    rules_this_phase = &rules[..];
                 }
 
-                let last_fixes = Vec::new();
-                for rule in rules_this_phase {
-                    // Performance: After first loop pass, skip rules that don't do fixes. Any
-                    // results returned won't be seen by the user anyway (linting errors ADDED by
-                    // rules changing SQL, are not reported back to the user - only initial linting
-                    // errors), so there's absolutely no reason to run them.
-                    if fix && !is_first_linter_pass && !rule.is_fix_compatible() {
-                        continue;
-                    }
-
-                    let (linting_errors, fixes) = rule.crawl(
-                        tables,
-                        &self.config.dialect,
-                        fix,
-                        templated_file,
-                        tree.clone(),
-                        &self.config,
-                    );
-
-                    if is_first_linter_pass {
-                        initial_linting_errors.extend(linting_errors);
-                    }
-
-                    if fix && !fixes.is_empty() {
-                        // Do some sanity checks on the fixes before applying.
-                        // let anchor_info = BaseSegment.compute_anchor_edit_info(fixes);
-
-                        // This is the happy path. We have fixes, now we want to apply them.
-
-                        if fixes == last_fixes {
-                            eprintln!(
-                                "One fix for {} not applied, it would re-cause the same error.",
-                                rule.code()
-                            );
+                let loop_start = tree.clone();
+                let changed = loop {
+                    let mut changed = Vec::new();
+                    let mut added_versions = Vec::new();
+                    let mut looping_this_loop = Vec::new();
+
+                    for rule in rules_this_phase {
+                        // Performance: After first loop pass, skip rules that don't do fixes. Any
+                        // results returned won't be seen by the user anyway (linting errors ADDED by
+                        // rules changing SQL, are not reported back to the user - only initial linting
+                        // errors), so there's absolutely no reason to run them.
+                        if fix && !is_first_linter_pass && !rule.is_fix_compatible() {
                             continue;
                         }
 
-                        let mut anchor_info = compute_anchor_edit_info(fixes.into_iter());
-                        let (new_tree, _, _, _valid) = tree.apply_fixes(&mut anchor_info);
-
-                        if false {
-                            println!(
-                                "Fixes for {rule:?} not applied, as it would result in an \
-                                 unparsable file. Please report this as a bug with a minimal \
-                                 query which demonstrates this warning.",
-                            );
+                        let (linting_errors, fixes) = rule.crawl(
+                            tables,
+                            &config.dialect,
+                            fix,
+                            templated_file,
+                            tree.clone(),
+                            config,
+                        );
+
+                        if is_first_linter_pass && !check_each_fix {
+                            initial_linting_errors.extend(linting_errors);
                         }
 
-                        let loop_check_tuple =
-                            (new_tree.raw().to_smolstr(), new_tree.get_source_fixes());
+                        if fix && !fixes.is_empty() {
+                            // Do some sanity checks on the fixes before applying.
+                            // let anchor_info = BaseSegment.compute_anchor_edit_info(fixes);
+
+                            // This is the happy path. We have fixes, now we want to apply them.
+                            let mut anchor_info = compute_anchor_edit_info(fixes.into_iter());
+                            let (new_tree, _, _, _) = tree.apply_fixes(&mut anchor_info);
+
+                            if check_each_fix
+                                && !Self::is_parsable_as(
+                                    tables,
+                                    &new_tree,
+                                    config,
+                                    unparsable_count,
+                                )
+                            {
+                                self.dispatch_file_warning(
+                                    templated_file.name(),
+                                    &format!(
+                                        "Fixes for {} not applied, as they would result in an \
+                                         unparsable file. Please report this as a bug with a \
+                                         minimal query which demonstrates this warning.",
+                                        rule.code()
+                                    ),
+                                );
+                                continue;
+                            }
+
+                            let loop_check_tuple =
+                                (new_tree.raw().to_smolstr(), new_tree.get_source_fixes());
+
+                            if previous_versions.insert(loop_check_tuple.clone()) {
+                                added_versions.push(loop_check_tuple);
+                                tree = new_tree;
+                                changed.push(rule.code());
+                                continue;
+                            }
+
+                            // The fix would re-cause an earlier version of the file.
+                            looping_this_loop.push(rule.code());
+                        }
+                    }
 
-                        if previous_versions.insert(loop_check_tuple) {
-                            tree = new_tree;
-                            changed = true;
-                            continue;
+                    // Re-parsing after every fix is costly, so the fixes of a whole loop are
+                    // checked at once. If they broke the file, the loop is run again checking
+                    // each rule's fixes, which is kept up for the rest of the file.
+                    if !check_each_fix
+                        && !changed.is_empty()
+                        && !Self::is_parsable_as(tables, &tree, config, unparsable_count)
+                    {
+                        tree = loop_start.clone();
+                        for version in &added_versions {
+                            previous_versions.remove(version);
                         }
+                        check_each_fix = true;
+                        continue;
                     }
-                }
 
-                if fix && !changed {
+                    looping_rules.extend(looping_this_loop);
+                    break changed;
+                };
+
+                if fix && changed.is_empty() {
                     break;
                 }
+
+                if *phase == LintPhase::Main && loop_ + 1 == loop_limit {
+                    runaway_rules.extend(changed);
+                }
             }
         }
 
+        if !looping_rules.is_empty() {
+            initial_linting_errors.push(Self::fix_loop_violation(format!(
+                "Fixes for {} were not applied, as they would undo earlier fixes. These rules \
+                 are likely in conflict.",
+                looping_rules.into_iter().sorted().join(", ")
+            )));
+        }
+
+        if !runaway_rules.is_empty() {
+            initial_linting_errors.push(Self::fix_loop_violation(format!(
+                "Fixes did not settle before reaching `runaway_limit` ({loop_limit}), {} kept \
+                 changing the file. Increase the limit or check these rules for conflicts.",
+                runaway_rules.into_iter().sorted().join(", ")
+            )));
+        }
+
         (tree, ignore_mask, initial_linting_errors)
     }
 
+    /// A warning about the fix loop of a file, reported at its start.
+    fn fix_loop_violation(description: String) -> SQLLintError {
+        SQLBaseError {
+            fatal: false,
+            ignore: false,
+            warning: true,
+            line_no: 1,
+            line_pos: 1,
+            description,
+            rule: Some(ErrorStructRule {
+                name: "fix_loop",
+                code: "LOOP",
+            }),
+            source_slice: 0..0,
+            fixable: false,
+            fixes: Vec::new(),
+        }
+        .into()
+    }
+
+    /// Count the unparsable sections of a tree.
+    fn count_unparsable(tree: &ErasedSegment) -> usize {
+        tree.recursive_crawl(
+            const { &SyntaxSet::single(SyntaxKind::Unparsable) },
+            true,
+            &SyntaxSet::EMPTY,
+            true,
+        )
+        .len()
+    }
+
+    /// Whether the SQL of a fixed tree still parses, with no more unparsable
+    /// sections than the file had to begin with.
+    fn is_parsable_as(
+        tables: &Tables,
+        tree: &ErasedSegment,
+        config: &FluffConfig,
+        unparsable_count: usize,
+    ) -> bool {
+        Self::reparse_unparsable_count(tables, tree, config)
+            .is_some_and(|count| count <= unparsable_count)
+    }
+
+    fn dispatch_file_warning(&self, f_name: &str, message: &str) {
+        if let Some(formatter) = &self.formatter {
+            formatter.dispatch_file_warning(f_name, message);
+        }
+    }
+
+    /// Parse the SQL of a tree from scratch and count its unparsable sections,
+    /// returning `None` if it cannot be lexed or parsed at all.
+    fn reparse_unparsable_count(
+        tables: &Tables,
+        tree: &ErasedSegment,
+        config: &FluffConfig,
+    ) -> Option<usize> {
+        let raw = tree.raw();
+        let (tokens, lex_errors) = config
+            .dialect
+            .lexer()
+            .lex(tables, StringOrTemplate::String(raw.as_str()))
+            .ok()?;
+        if !lex_errors.is_empty() {
+            return None;
+        }
+
+        let (tree, parse_errors) = Self::parse_tokens(tables, &tokens, config, None);
+        if !parse_errors.is_empty() {
+            return None;
+        }
+
+        Some(tree.as_ref().map_or(0, Self::count_unparsable))
+    }
+
     /// Template the file.
     pub fn render_string(
         &self,
@@ -376,43 +776,66 @@ impl Linter {
     ) -> Result<RenderedFile, SQLFluffUserError> {
         let sql = Self::normalise_newlines(sql);
 
+        // Scan the raw file for config commands.
+        let mut config = config.clone();
+        let mut templater_violations = config.process_raw_file_for_config(&sql);
+
         if let Some(error) = config.verify_dialect_specified() {
             return Err(error);
         }
 
-        let templater_violations = vec![];
-        match self.templater.process(
+        let templated_file = match self.templater.process(
             sql.as_ref(),
             filename.as_str(),
-            Some(config),
+            Some(&config),
             &self.formatter,
         ) {
-            Ok(templated_file) => Ok(RenderedFile {
-                templated_file,
-                templater_violations,
-                filename,
-                source_str: sql.to_string(),
+            Ok(templated_file) => templated_file,
+            // A file which can't be templated is reported, but not linted.
+            Err(error) => {
+                templater_violations.push(Self::templating_violation(error.value));
+                TemplatedFile::new(sql.to_string(), filename.clone(), None, None, None).unwrap()
+            }
+        };
+
+        Ok(RenderedFile {
+            templated_file,
+            templater_violations,
+            config,
+            filename,
+            source_str: sql.to_string(),
+        })
+    }
+
+    /// An error rendering a file, reported at its start.
+    fn templating_violation(description: String) -> SQLBaseError {
+        SQLBaseError {
+            fatal: true,
+            ignore: false,
+            warning: false,
+            line_no: 1,
+            line_pos: 1,
+            description,
+            rule: Some(ErrorStructRule {
+                name: "templating",
+                code: "TMP",
             }),
-            Err(err) => Err(SQLFluffUserError::new(format!(
-                "Failed to template file {} with error {:?}",
-                filename, err
-            ))),
+            source_slice: 0..0,
+            fixable: false,
+            fixes: Vec::new(),
         }
     }
 
     /// Parse a rendered file.
     pub fn parse_rendered(&self, tables: &Tables, rendered: RenderedFile) -> ParsedString {
-        let violations = rendered.templater_violations.clone();
-        if !violations.is_empty() {
-            unimplemented!()
-        }
+        let mut violations = rendered.templater_violations;
+        let templated = violations.iter().all(|violation| !violation.fatal);
 
-        let mut violations = Vec::new();
-        let tokens = if rendered.templated_file.is_templated() {
+        let tokens = if templated && rendered.templated_file.is_templated() {
             let (t, lvs) = Self::lex_templated_file(
                 tables,
                 rendered.templated_file.clone(),
-                &self.config.dialect,
+                &rendered.config.dialect,
             );
             if !lvs.is_empty() {
                 unimplemented!("violations.extend(lvs);")
@@ -427,7 +850,7 @@ impl Linter {
             let (p, pvs) = Self::parse_tokens(
                 tables,
                 &token_list,
-                &self.config,
+                &rendered.config,
                 Some(rendered.filename.to_string()),
             );
             parsed = p;
@@ -440,6 +863,7 @@ impl Linter {
             tree: parsed,
             violations,
             templated_file: rendered.templated_file,
+            config: rendered.config,
             filename: rendered.filename,
             source_str: rendered.source_str,
         }
@@ -495,7 +919,7 @@ impl Linter {
     }
 
     /// Normalise newlines to unix-style line endings.
-    fn normalise_newlines(string: &str) -> Cow<str> {
+    pub fn normalise_newlines(string: &str) -> Cow<str> {
         lazy_regex::regex!("\r\n|\r").replace_all(string, "\n")
     }
 
@@ -638,13 +1062,44 @@ impl Linter {
 
     pub fn config_mut(&mut self) -> &mut FluffConfig {
         self.rules = OnceLock::new();
+        self.rule_packs.get_mut().unwrap().clear();
         &mut self.config
     }
 
+    /// Only lint files with changes, and only report violations on the changed
+    /// lines of those files.
+    pub fn set_changed_lines(&mut self, changed_lines: ChangedLines) {
+        self.changed_lines = Some(changed_lines);
+    }
+
+    /// Don't report the violations recorded in a baseline.
+    pub fn set_baseline(&mut self, baseline: Baseline) {
+        self.baseline = Some(baseline);
+    }
+
     pub fn rules(&self) -> &[ErasedRule] {
         self.rules.get_or_init(|| self.get_rulepack().rules)
     }
 
+    /// The rules for a file, which only differ from the linter's own rules
+    /// when the file is linted with a different config.
+    fn rules_for(&self, config: &FluffConfig) -> Cow<'_, [ErasedRule]> {
+        if *config == self.config {
+            return Cow::Borrowed(self.rules());
+        }
+
+        let rule_packs = self.rule_packs.lock().unwrap();
+        if let Some((_, rules)) = rule_packs.iter().find(|(other, _)| other == config) {
+            return Cow::Owned(rules.clone());
+        }
+        drop(rule_packs);
+
+        let rules = get_ruleset().get_rulepack(config).rules;
+        let mut rule_packs = self.rule_packs.lock().unwrap();
+        rule_packs.push((config.clone(), rules.clone()));
+        Cow::Owned(rules)
+    }
+
     pub fn formatter(&self) -> Option<&Arc<dyn Formatter>> {
         self.formatter.as_ref()
     }
@@ -656,10 +1111,21 @@ impl Linter {
 
 #[cfg(test)]
 mod tests {
-    use sqruff_lib_core::parser::segments::base::Tables;
+    use std::path::Path;
+
+    use ahash::AHashMap;
+    use itertools::Itertools;
+    use sqruff_lib_core::dialects::init::DialectKind;
+    use sqruff_lib_core::dialects::syntax::{SyntaxKind, SyntaxSet};
+    use sqruff_lib_core::lint_fix::LintFix;
+    use sqruff_lib_core::parser::segments::base::{SegmentBuilder, Tables};
 
-    use crate::core::config::FluffConfig;
+    use crate::core::config::{FluffConfig, Value};
+    use crate::core::linter::changed_lines::ChangedLines;
     use crate::core::linter::core::Linter;
+    use crate::core::rules::base::{Erased, ErasedRule, LintResult, Rule, RuleGroups};
+    use crate::core::rules::context::RuleContext;
+    use crate::core::rules::crawlers::{Crawler, SegmentSeekerCrawler};
 
     fn normalise_paths(paths: Vec<String>) -> Vec<String> {
         paths
@@ -735,7 +1201,6 @@ mod tests {
         );
     }
 
-    // test__linter__skip_large_bytes
     // test__linter__path_from_paths__not_exist
     // test__linter__path_from_paths__not_exist_ignore
     // test__linter__path_from_paths__explicit_ignore
@@ -750,8 +1215,59 @@ mod tests {
     // test__linter__linting_result_get_violations
     // test__linter__linting_parallel_thread
     // test_lint_path_parallel_wrapper_exception
-    // test__linter__get_runner_processes
     // test__linter__linting_unexpected_error_handled_gracefully
+    #[test]
+    fn test_linter_skip_large_bytes() {
+        let config = FluffConfig::from_source(
+            "[sqruff]\nlarge_file_skip_byte_limit = 10\nlarge_file_skip_char_limit = 0\n",
+        );
+        let linter = Linter::new(config.clone(), None, None);
+        let skip = linter
+            .render_file(
+                "test/fixtures/linter/indentation_errors.sql".into(),
+                &config,
+            )
+            .unwrap_err();
+        assert!(skip.value.contains("over the limit of 10 bytes"));
+
+        let config = FluffConfig::from_source(
+            "[sqruff]\nlarge_file_skip_byte_limit = 0\nlarge_file_skip_char_limit = 10\n",
+        );
+        let skip = linter
+            .render_file(
+                "test/fixtures/linter/indentation_errors.sql".into(),
+                &config,
+            )
+            .unwrap_err();
+        assert!(skip.value.contains("over the limit of 10 characters"));
+
+        let config = FluffConfig::from_source("[sqruff]\nlarge_file_skip_byte_limit = 0\n");
+        assert!(linter
+            .render_file(
+                "test/fixtures/linter/indentation_errors.sql".into(),
+                &config,
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn test_linter_get_runner_processes() {
+        let cpus = std::thread::available_parallelism().unwrap().get();
+
+        for (processes, expected) in [
+            (5, 5),
+            (1, 1),
+            (0, cpus),
+            (-1, (cpus - 1).max(1)),
+            (-1000, 1),
+        ] {
+            let mut config = FluffConfig::new(<_>::default(), None, None);
+            config.set_value("processes", "core", Value::Int(processes));
+            let linter = Linter::new(config, None, None);
+            assert_eq!(linter.processes(), expected, "processes = {processes}");
+        }
+    }
+
     #[test]
     fn test_linter_empty_file() {
         let linter = Linter::new(FluffConfig::new(<_>::default(), None, None), None, None);
@@ -761,6 +1277,147 @@ mod tests {
         assert!(parsed.violations.is_empty());
     }
 
+    #[test]
+    fn test_linter_inline_config() {
+        let linter = Linter::new(FluffConfig::new(<_>::default(), None, None), None, None);
+
+        let sql = "-- sqruff:dialect:postgres\n-- sqlfluff:rules:CP01\n\
+                   -- sqruff:rules:capitalisation.keywords:capitalisation_policy:upper\n\
+                   select a::int  from t\n";
+        let tables = Tables::default();
+        let parsed = linter.parse_string(&tables, sql, None).unwrap();
+        assert_eq!(parsed.config.get_dialect().name, DialectKind::Postgres);
+        assert!(parsed.violations.is_empty());
+
+        let linted = linter.lint_string(sql, None, false);
+        let codes = linted
+            .violations
+            .iter()
+            .map(|violation| violation.rule_code())
+            .collect_vec();
+        assert_eq!(codes, ["CP01", "CP01"]);
+
+        // The linter's own config is untouched.
+        assert_eq!(linter.config().get_dialect().name, DialectKind::Ansi);
+
+        // Commands which can't be applied are reported on their own lines.
+        let sql = "select 1\n-- sqruff:dialect:not_a_dialect\n";
+        let parsed = linter.parse_string(&tables, sql, None).unwrap();
+        assert_eq!(parsed.config.get_dialect().name, DialectKind::Ansi);
+        let violations = parsed
+            .violations
+            .iter()
+            .map(|violation| (violation.rule_code(), violation.line_no))
+            .collect_vec();
+        assert_eq!(violations, [("CFG", 2)]);
+        assert_eq!(parsed.violations[0].source_slice, 9..40);
+    }
+
+    #[test]
+    fn test_linter_config_per_directory() {
+        let mut linter = Linter::new(FluffConfig::new(<_>::default(), None, None), None, None);
+
+        // The nested config switches to postgres and only enables CP01.
+        let result = linter.lint_paths(vec!["test/fixtures/config/nested".into()], false, &|_| {
+            false
+        });
+        let files = result.paths[0].files.iter().collect_vec();
+        assert_eq!(files.len(), 2);
+        for file in files {
+            let codes = file
+                .violations
+                .iter()
+                .map(|violation| violation.rule_code())
+                .collect_vec();
+            if file.path.ends_with("upper.sql") {
+                // The deeper config overrides the capitalisation policy.
+                assert_eq!(codes, ["CP01"], "{}", file.path);
+            } else {
+                assert!(codes.is_empty(), "{}: {codes:?}", file.path);
+            }
+        }
+    }
+
+    #[test]
+    fn test_linter_runaway_limit() {
+        let sql = "SELECT 1 from t\n";
+
+        // CP01 fixes the file in one loop, but needs a second one to confirm it.
+        let config = FluffConfig::from_source("[sqruff]\nrules = CP01\nrunaway_limit = 1\n");
+        let linted = Linter::new(config, None, None).lint_string(sql, None, true);
+        let loops = linted
+            .violations
+            .iter()
+            .filter(|violation| violation.rule_code() == "LOOP")
+            .collect_vec();
+        assert_eq!(loops.len(), 1);
+        assert!(loops[0].warning);
+        assert!(loops[0].description.contains("CP01 kept changing"));
+
+        let config = FluffConfig::from_source("[sqruff]\nrules = CP01\n");
+        let linted = Linter::new(config, None, None).lint_string(sql, None, true);
+        assert!(linted
+            .violations
+            .iter()
+            .all(|violation| violation.rule_code() != "LOOP"));
+    }
+
+    #[test]
+    fn test_linter_warnings() {
+        let config = FluffConfig::from_source("[sqruff]\nrules = LT01,LT12\nwarnings = LT01\n");
+        let linted = Linter::new(config, None, None).lint_string("SELECT   1", None, false);
+
+        let violations = linted
+            .violations
+            .iter()
+            .map(|violation| (violation.rule_code(), violation.warning))
+            .collect_vec();
+        assert_eq!(violations, [("LT01", true), ("LT12", false)]);
+    }
+
+    #[test]
+    fn test_linter_changed_lines() {
+        let diff = "--- a/a.sql\n+++ b/a.sql\n@@ -2 +2 @@\n-FROM t\n+FROM   t\n";
+        let config = FluffConfig::from_source("[sqruff]\nrules = LT01\n");
+        let mut linter = Linter::new(config, None, None);
+        linter.set_changed_lines(ChangedLines::from_diff(Path::new(""), diff));
+
+        let linted = linter.lint_string("SELECT   1\nFROM   t\n", Some("a.sql".into()), false);
+        let lines = linted
+            .violations
+            .iter()
+            .map(|violation| violation.line_no)
+            .collect_vec();
+        assert_eq!(lines, [2]);
+    }
+
+    #[test]
+    fn test_linter_templating_error() {
+        let config = FluffConfig::from_source("[sqruff]\ntemplater = jinja\nrules = LT01\n");
+        let linter = Linter::new(config, None, None);
+
+        let linted = linter.lint_string("SELECT {{ 1\n", None, false);
+        let codes = linted
+            .violations
+            .iter()
+            .map(|v| v.rule_code())
+            .collect_vec();
+        assert_eq!(codes, ["TMP"]);
+
+        let config = FluffConfig::from_source(
+            "[sqruff]\ntemplater = jinja\nrules = LT01\nignore = templating\n",
+        );
+        let linted = Linter::new(config, None, None).lint_string("SELECT {{ 1\n", None, false);
+        assert!(linted.violations.is_empty());
+    }
+
+    #[test]
+    fn test_linter_ignore() {
+        let config = FluffConfig::from_source("[sqruff]\nrules = LT01\nignore = linting\n");
+        let linted = Linter::new(config, None, None).lint_string("SELECT   1\n", None, false);
+        assert!(linted.violations.is_empty());
+    }
+
     // test__linter__mask_templated_violations
     // test__linter__encoding
     // test_delayed_exception
@@ -793,4 +1450,96 @@ mod tests {
 
         assert_eq!(Linter::normalise_newlines(in_str), out_str);
     }
+
+    /// A rule whose fix misspells `SELECT`, leaving a file which no longer parses.
+    #[derive(Clone, Debug)]
+    struct RuleBreaksSelect;
+
+    impl Rule for RuleBreaksSelect {
+        fn load_from_config(
+            &self,
+            _config: &AHashMap<String, Value>,
+        ) -> Result<ErasedRule, String> {
+            Ok(RuleBreaksSelect.erased())
+        }
+
+        fn name(&self) -> &'static str {
+            "test.breaks_select"
+        }
+
+        fn description(&self) -> &'static str {
+            ""
+        }
+
+        fn long_description(&self) -> &'static str {
+            ""
+        }
+
+        fn groups(&self) -> &'static [RuleGroups] {
+            &[RuleGroups::All]
+        }
+
+        fn eval(&self, context: RuleContext) -> Vec<LintResult> {
+            if !context.segment.raw().eq_ignore_ascii_case("SELECT") {
+                return Vec::new();
+            }
+
+            let misspelt = SegmentBuilder::keyword(context.tables.next_id(), "SELEC");
+            vec![LintResult::new(
+                Some(context.segment.clone()),
+                vec![LintFix::replace(
+                    context.segment.clone(),
+                    vec![misspelt],
+                    None,
+                )],
+                None,
+                None,
+            )]
+        }
+
+        fn crawl_behaviour(&self) -> Crawler {
+            SegmentSeekerCrawler::new(const { SyntaxSet::new(&[SyntaxKind::Keyword]) }).into()
+        }
+    }
+
+    #[test]
+    fn test_linter_rolls_back_fixes_which_break_parsing() {
+        let mut linter = Linter::new(
+            FluffConfig::from_source("[sqruff]\ndialect = ansi\nrules = LT01\n"),
+            None,
+            None,
+        );
+        let rules = [RuleBreaksSelect.erased()]
+            .into_iter()
+            .chain(linter.rules().iter().cloned())
+            .collect_vec();
+        linter.rules = rules.into();
+
+        // The fixes of the other rules in the same loop still apply.
+        let fixed = linter.lint_string("SELECT   1\n", None, true).fix_string();
+        assert_eq!(fixed, "SELECT 1\n");
+    }
+
+    #[test]
+    fn test_linter_fix_unparsable() {
+        let sql = "SELECT   1 FROM t WHERE a = = b\n";
+
+        let linter = Linter::new(
+            FluffConfig::from_source("[sqruff]\ndialect = ansi\nrules = LT01\n"),
+            None,
+            None,
+        );
+        let fixed = linter.lint_string(sql, None, true).fix_string();
+        assert_eq!(fixed, sql);
+
+        let linter = Linter::new(
+            FluffConfig::from_source(
+                "[sqruff]\ndialect = ansi\nrules = LT01\nfix_even_unparsable = True\n",
+            ),
+            None,
+            None,
+        );
+        let fixed = linter.lint_string(sql, None, true).fix_string();
+        assert!(fixed.starts_with("SELECT 1 FROM t"));
+    }
 }
@@ -5,7 +5,7 @@ use itertools::Itertools;
 use rustc_hash::FxHashSet;
 use sqruff_lib_core::errors::SQLBaseError;
 use sqruff_lib_core::parser::segments::fix::FixPatch;
-use sqruff_lib_core::templaters::base::{RawFileSlice, TemplatedFile};
+use sqruff_lib_core::templaters::base::{RawFileSlice, TemplatedFile, TemplatedFileSlice};
 
 #[derive(Debug, Default)]
 pub struct LintedFile {
@@ -87,16 +87,92 @@ impl LintedFile {
         )
     }
 
+    /// The patches [`LintedFile::fix_string`] applies to the source, in order
+    /// and without overlaps.
+    pub fn source_patches(&self) -> Vec<FixPatch> {
+        let patches = Self::generate_source_patches(self.patches.clone(), &self.templated_file);
+        let slices = Self::slice_source_file_using_patches(
+            patches.clone(),
+            self.templated_file.source_only_slices(),
+            &self.templated_file.source_str,
+        );
+
+        slices
+            .iter()
+            .filter_map(|slice| patches.iter().find(|patch| patch.source_slice == *slice))
+            .cloned()
+            .collect()
+    }
+
     fn generate_source_patches(
         patches: Vec<FixPatch>,
-        _templated_file: &TemplatedFile,
+        templated_file: &TemplatedFile,
     ) -> Vec<FixPatch> {
         let mut filtered_source_patches = Vec::new();
         let mut dedupe_buffer = FxHashSet::default();
 
-        for patch in patches {
-            if dedupe_buffer.insert(patch.dedupe_tuple()) {
+        for mut patch in patches {
+            // Patches running backwards through the source can only come from
+            // looped sections of a template. There's no safe way to apply them.
+            if patch.source_slice.start > patch.source_slice.end {
+                continue;
+            }
+
+            // Similarly, anything rendered more than once can't be fixed in one place.
+            if Self::is_source_slice_repeated(&patch.source_slice, templated_file) {
+                continue;
+            }
+
+            let local_raw_slices =
+                templated_file.raw_slices_spanning_source_slice(&patch.source_slice);
+            let enclosing_slice = local_raw_slices.first();
+
+            // Deduped patches are ignored.
+            if !dedupe_buffer.insert(patch.dedupe_tuple()) {
+                continue;
+            }
+
+            // We now evaluate patches in the source-space for whether they overlap
+            // or disrupt any templated sections unless designed to do so.
+            if local_raw_slices.is_empty()
+                || local_raw_slices
+                    .iter()
+                    .all(|slice| slice.slice_type == "literal")
+            {
+                // Easy case: the patch is fully within literal slices.
                 filtered_source_patches.push(patch);
+            } else if enclosing_slice.is_some_and(|slice| {
+                patch.source_slice.start == patch.source_slice.end
+                    && patch.source_slice.start == slice.source_idx
+            }) {
+                // Zero length insertions at the start of a slice are safe.
+                filtered_source_patches.push(patch);
+            } else if local_raw_slices
+                .iter()
+                .all(|slice| slice.slice_type == "templated")
+            {
+                // Patches entirely within templated code are skipped.
+                continue;
+            } else if local_raw_slices.len() > 2
+                || patch.templated_str.is_empty()
+                || !patch.source_str.contains(&patch.templated_str)
+            {
+                // Anything spanning several slices, inserting into a templated
+                // section, or not recognisably in the source is unsafe.
+                continue;
+            } else {
+                // The patch touches a templated section but we can still locate
+                // the literal portion in the source. Relocate it if unambiguous.
+                let positions = patch
+                    .source_str
+                    .match_indices(&patch.templated_str)
+                    .map(|(idx, _)| idx)
+                    .collect_vec();
+                if let [offset] = positions[..] {
+                    let start = patch.source_slice.start + offset;
+                    patch.source_slice = start..start + patch.templated_str.len();
+                    filtered_source_patches.push(patch);
+                }
             }
         }
 
@@ -104,6 +180,27 @@ impl LintedFile {
         filtered_source_patches
     }
 
+    /// Whether any part of the source slice is rendered more than once, as
+    /// happens in the body of a loop.
+    fn is_source_slice_repeated(
+        source_slice: &Range<usize>,
+        templated_file: &TemplatedFile,
+    ) -> bool {
+        let overlapping = |tfs: &&TemplatedFileSlice| {
+            tfs.source_slice.start < source_slice.end.max(source_slice.start + 1)
+                && source_slice.start < tfs.source_slice.end
+        };
+
+        templated_file
+            .sliced_file
+            .iter()
+            .filter(overlapping)
+            .map(|tfs| tfs.source_slice.clone())
+            .duplicates()
+            .next()
+            .is_some()
+    }
+
     ///  Use patches to safely slice up the file before fixing.
     ///
     ///  This uses source only slices to avoid overwriting sections
@@ -185,9 +282,8 @@ impl LintedFile {
 }
 
 #[cfg(test)]
+#[allow(clippy::single_range_in_vec_init)]
 mod test {
-    use sqruff_lib_core::templaters::base::TemplatedFileSlice;
-
     use super::*;
 
     /// Test _build_up_fixed_source_string. This is part of fix_string().
@@ -73,7 +73,8 @@ impl LintResult {
                 this.rule = Some(ErrorStructRule {
                     name: rule.name(),
                     code: rule.code(),
-                })
+                });
+                this.fixes = self.fixes.iter().flat_map(LintFix::source_edits).collect();
             })
             .into()
     }
@@ -434,15 +435,18 @@ impl RuleSet {
             let rule = self.register[code].rule_class.clone();
             let rule_config_ref = rule.config_ref();
 
-            let tmp = AHashMap::new();
-
-            let specific_rule_config = rules
-                .get(rule_config_ref)
-                .and_then(|section| section.as_map())
-                .unwrap_or(&tmp);
+            // Rule options may be given under the rule name or its code, with
+            // the code taking precedence.
+            let specific_rule_config: AHashMap<_, _> =
+                [rule_config_ref, code.to_lowercase().as_str()]
+                    .into_iter()
+                    .filter_map(|key| rules.get(key).and_then(|section| section.as_map()))
+                    .flatten()
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect();
 
             // TODO fail the rulepack if any need unwrapping
-            instantiated_rules.push(rule.load_from_config(specific_rule_config).unwrap());
+            instantiated_rules.push(rule.load_from_config(&specific_rule_config).unwrap());
         }
 
         RulePack {
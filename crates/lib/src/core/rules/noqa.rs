@@ -1,7 +1,8 @@
-use ahash::HashSet;
+use ahash::{AHashMap, HashSet};
 use itertools::Itertools;
 use sqruff_lib_core::dialects::syntax::{SyntaxKind, SyntaxSet};
-use sqruff_lib_core::errors::SQLBaseError;
+use sqruff_lib_core::errors::{ErrorStructRule, SQLBaseError};
+use sqruff_lib_core::lint_fix::LintFix;
 use sqruff_lib_core::parser::segments::base::ErasedSegment;
 
 /// The NoQA directive is a way to disable specific rules or all rules for a specific line or range of lines.
@@ -38,6 +39,13 @@ use sqruff_lib_core::parser::segments::base::ErasedSegment;
 /// -- Enforce all rules from this line forward
 /// SELECT col_a a FROM foo -- noqa: enable=all
 /// ```
+///
+/// ## Unused directives
+///
+/// Setting `warn_unused_ignores = True` reports a `NOQA` warning for each directive which did not
+/// ignore any errors, or which refers to an unknown rule. Directives are only checked when all the
+/// rules they name ran, so restricting `rules` doesn't flag the others. Fixing removes these
+/// comments, unless they also hold other text.
 #[derive(Eq, PartialEq, Debug)]
 enum NoQADirective {
     LineIgnoreAll(LineIgnoreAll),
@@ -47,41 +55,86 @@ enum NoQADirective {
 }
 
 impl NoQADirective {
-    /// validate checks if the NoQADirective is valid by checking it against a rule set and returns
-    /// error if it is valid against a set of errors rules
-    #[allow(dead_code)]
-    fn validate_against_rules(&self, available_rules: &HashSet<&str>) -> Result<(), SQLBaseError> {
-        fn check_rules(
-            rules: &HashSet<String>,
-            available_rules: &HashSet<&str>,
-        ) -> Result<(), SQLBaseError> {
-            for rule in rules {
-                if !available_rules.contains(rule.as_str()) {
-                    return Err(SQLBaseError {
-                        fatal: true,
-                        ignore: false,
-                        warning: false,
-                        line_no: 0,
-                        line_pos: 0,
-                        description: format!("Rule {} not found in rule set", rule),
-                        rule: None,
-                        source_slice: Default::default(),
-                        fixable: false,
-                    });
-                }
-            }
-            Ok(())
+    fn line_no(&self) -> usize {
+        match self {
+            NoQADirective::LineIgnoreAll(LineIgnoreAll { line_no, .. })
+            | NoQADirective::LineIgnoreRules(LineIgnoreRules { line_no, .. })
+            | NoQADirective::RangeIgnoreAll(RangeIgnoreAll { line_no, .. })
+            | NoQADirective::RangeIgnoreRules(RangeIgnoreRules { line_no, .. }) => *line_no,
+        }
+    }
+
+    fn line_pos(&self) -> usize {
+        match self {
+            NoQADirective::LineIgnoreAll(LineIgnoreAll { line_pos, .. })
+            | NoQADirective::LineIgnoreRules(LineIgnoreRules { line_pos, .. })
+            | NoQADirective::RangeIgnoreAll(RangeIgnoreAll { line_pos, .. })
+            | NoQADirective::RangeIgnoreRules(RangeIgnoreRules { line_pos, .. }) => *line_pos,
         }
+    }
 
+    fn raw_string(&self) -> &str {
         match self {
-            NoQADirective::LineIgnoreAll(_) => Ok(()),
-            NoQADirective::LineIgnoreRules(LineIgnoreRules { rules, .. }) => {
-                check_rules(rules, available_rules)
-            }
-            NoQADirective::RangeIgnoreAll(_) => Ok(()),
-            NoQADirective::RangeIgnoreRules(RangeIgnoreRules { rules, .. }) => {
-                check_rules(rules, available_rules)
-            }
+            NoQADirective::LineIgnoreAll(LineIgnoreAll { raw_string, .. })
+            | NoQADirective::LineIgnoreRules(LineIgnoreRules { raw_string, .. })
+            | NoQADirective::RangeIgnoreAll(RangeIgnoreAll { raw_string, .. })
+            | NoQADirective::RangeIgnoreRules(RangeIgnoreRules { raw_string, .. }) => raw_string,
+        }
+    }
+
+    /// Enable directives end a range, so they never mask a violation themselves.
+    fn is_enable(&self) -> bool {
+        matches!(
+            self,
+            NoQADirective::RangeIgnoreAll(RangeIgnoreAll {
+                action: IgnoreAction::Enable,
+                ..
+            }) | NoQADirective::RangeIgnoreRules(RangeIgnoreRules {
+                action: IgnoreAction::Enable,
+                ..
+            })
+        )
+    }
+
+    /// Whether the comment holds nothing but the directive, so that it can be
+    /// removed without losing any other text.
+    fn is_whole_comment(&self) -> bool {
+        self.raw_string()
+            .split("--")
+            .filter(|part| !part.trim().is_empty())
+            .count()
+            == 1
+    }
+
+    /// The rules named by the directive, or `None` if it applies to all rules.
+    fn rules(&self) -> Option<&HashSet<String>> {
+        match self {
+            NoQADirective::LineIgnoreRules(LineIgnoreRules { rules, .. })
+            | NoQADirective::RangeIgnoreRules(RangeIgnoreRules { rules, .. }) => Some(rules),
+            NoQADirective::LineIgnoreAll(_) | NoQADirective::RangeIgnoreAll(_) => None,
+        }
+    }
+
+    /// unknown_rules returns the rules of the directive which are not in the available rule set
+    fn unknown_rules(&self, available_rules: &HashSet<&str>) -> Vec<&str> {
+        self.rules()
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .filter(|rule| !available_rules.contains(rule))
+            .sorted()
+            .collect()
+    }
+
+    /// Whether every rule the directive applies to ran, so that it not masking any violation
+    /// means it is unused. Unknown rules never raise violations, so they count as having run.
+    /// Directives for all rules need every available rule to have run.
+    fn is_checked(&self, ran_rules: &HashSet<&str>, available_rules: &HashSet<&str>) -> bool {
+        match self.rules() {
+            Some(rules) => rules.iter().all(|rule| {
+                ran_rules.contains(rule.as_str()) || !available_rules.contains(rule.as_str())
+            }),
+            None => available_rules.iter().all(|rule| ran_rules.contains(rule)),
         }
     }
 
@@ -136,6 +189,7 @@ impl NoQADirective {
                                     rule: None,
                                     source_slice: Default::default(),
                                     fixable: false,
+                                    fixes: Vec::new(),
                                 })
                             } else {
                                 Ok(Some(NoQADirective::RangeIgnoreRules(RangeIgnoreRules {
@@ -175,6 +229,7 @@ impl NoQADirective {
                                     rule: None,
                                     source_slice: Default::default(),
                                     fixable: false,
+                                    fixes: Vec::new(),
                                 })
                             } else {
                                 Ok(Some(NoQADirective::RangeIgnoreRules(RangeIgnoreRules {
@@ -187,7 +242,11 @@ impl NoQADirective {
                             }
                         }
                     } else if !comment.is_empty() {
-                        let rules = comment.split(",").map_into().collect::<HashSet<String>>();
+                        let rules = comment
+                            .split(",")
+                            .map(|rule| rule.trim().to_string())
+                            .filter(|rule| !rule.is_empty())
+                            .collect::<HashSet<String>>();
                         if rules.is_empty() {
                             Err(SQLBaseError {
                                 fatal: true,
@@ -201,11 +260,12 @@ impl NoQADirective {
                                 rule: None,
                                 source_slice: Default::default(),
                                 fixable: false,
+                                fixes: Vec::new(),
                             })
                         } else {
                             return Ok(Some(NoQADirective::LineIgnoreRules(LineIgnoreRules {
                                 line_no,
-                                line_pos: 0,
+                                line_pos,
                                 raw_string: original_comment.into(),
                                 rules,
                             })));
@@ -223,6 +283,7 @@ impl NoQADirective {
                             rule: None,
                             source_slice: Default::default(),
                             fixable: false,
+                            fixes: Vec::new(),
                         })
                     }
                 } else {
@@ -238,6 +299,7 @@ impl NoQADirective {
                         rule: None,
                         source_slice: Default::default(),
                         fixable: false,
+                        fixes: Vec::new(),
                     })
                 }
             } else {
@@ -324,6 +386,7 @@ impl IgnoreMask {
                 rule: None,
                 source_slice: Default::default(),
                 fixable: false,
+                fixes: Vec::new(),
             })?
             .source_position();
         NoQADirective::parse_from_comment(comment_content, line_no, line_pos)
@@ -358,21 +421,28 @@ impl IgnoreMask {
     }
 
     /// is_masked returns true if the IgnoreMask masks the violation
-    /// TODO - The parsing should also return warnings for rules that aren't used
     pub fn is_masked(&self, violation: &SQLBaseError) -> bool {
-        fn is_masked_by_line_rules(ignore_mask: &IgnoreMask, violation: &SQLBaseError) -> bool {
-            for ignore in &ignore_mask.ignore_list {
+        self.masked_by(violation).is_some()
+    }
+
+    /// masked_by returns the index of the directive which masks the violation, if any
+    fn masked_by(&self, violation: &SQLBaseError) -> Option<usize> {
+        fn masked_by_line_rules(
+            ignore_mask: &IgnoreMask,
+            violation: &SQLBaseError,
+        ) -> Option<usize> {
+            for (idx, ignore) in ignore_mask.ignore_list.iter().enumerate() {
                 match ignore {
                     NoQADirective::LineIgnoreAll(LineIgnoreAll { line_no, .. }) => {
                         if violation.line_no == *line_no {
-                            return true;
+                            return Some(idx);
                         }
                     }
                     NoQADirective::LineIgnoreRules(LineIgnoreRules { line_no, rules, .. }) => {
                         if violation.line_no == *line_no {
                             if let Some(rule) = &violation.rule {
                                 if rules.contains(rule.code) {
-                                    return true;
+                                    return Some(idx);
                                 }
                             }
                         }
@@ -380,42 +450,45 @@ impl IgnoreMask {
                     _ => {}
                 }
             }
-            false
+            None
         }
 
-        /// is_masked_by_range returns true if the violation is masked by the RangeIgnoreRules and
-        /// RangeIgnoreAll components in the ignore mask
-        fn is_masked_by_range_rules(ignore_mask: &IgnoreMask, violation: &SQLBaseError) -> bool {
+        /// masked_by_range_rules returns the RangeIgnoreRules or RangeIgnoreAll directive in the
+        /// ignore mask which masks the violation, if any
+        fn masked_by_range_rules(
+            ignore_mask: &IgnoreMask,
+            violation: &SQLBaseError,
+        ) -> Option<usize> {
             // Collect RangeIgnore directives
             let mut directives = Vec::new();
 
-            for ignore in &ignore_mask.ignore_list {
+            for (idx, ignore) in ignore_mask.ignore_list.iter().enumerate() {
                 match ignore {
                     NoQADirective::RangeIgnoreAll(RangeIgnoreAll {
                         line_no, line_pos, ..
                     }) => {
-                        directives.push((line_no, line_pos, ignore));
+                        directives.push((line_no, line_pos, idx, ignore));
                     }
                     NoQADirective::RangeIgnoreRules(RangeIgnoreRules {
                         line_no, line_pos, ..
                     }) => {
-                        directives.push((line_no, line_pos, ignore));
+                        directives.push((line_no, line_pos, idx, ignore));
                     }
                     _ => {}
                 }
             }
 
             // Sort directives by line_no, line_pos
-            directives.sort_by(|(line_no1, line_pos1, _), (line_no2, line_pos2, _)| {
+            directives.sort_by(|(line_no1, line_pos1, ..), (line_no2, line_pos2, ..)| {
                 line_no1.cmp(line_no2).then(line_pos1.cmp(line_pos2))
             });
 
-            // Initialize state
-            let mut all_rules_disabled = false;
-            let mut disabled_rules = <HashSet<String>>::default();
+            // Initialize state, tracking the directive which disabled each rule
+            let mut all_rules_disabled_by = None;
+            let mut disabled_rules = AHashMap::<&str, usize>::default();
 
             // For each directive
-            for (line_no, line_pos, ignore) in directives {
+            for (line_no, line_pos, idx, ignore) in directives {
                 // Check if the directive is before the violation
                 if *line_no > violation.line_no {
                     break;
@@ -428,22 +501,22 @@ impl IgnoreMask {
                 match ignore {
                     NoQADirective::RangeIgnoreAll(RangeIgnoreAll { action, .. }) => match action {
                         IgnoreAction::Disable => {
-                            all_rules_disabled = true;
+                            all_rules_disabled_by = Some(idx);
                         }
                         IgnoreAction::Enable => {
-                            all_rules_disabled = false;
+                            all_rules_disabled_by = None;
                         }
                     },
                     NoQADirective::RangeIgnoreRules(RangeIgnoreRules { action, rules, .. }) => {
                         match action {
                             IgnoreAction::Disable => {
                                 for rule in rules {
-                                    disabled_rules.insert(rule.clone());
+                                    disabled_rules.insert(rule, idx);
                                 }
                             }
                             IgnoreAction::Enable => {
                                 for rule in rules {
-                                    disabled_rules.remove(rule);
+                                    disabled_rules.remove(rule.as_str());
                                 }
                             }
                         }
@@ -453,18 +526,149 @@ impl IgnoreMask {
             }
 
             // Check whether the violation is masked
-            if all_rules_disabled {
-                return true;
-            } else if let Some(rule) = &violation.rule {
-                if disabled_rules.contains(rule.code) {
-                    return true;
-                }
+            all_rules_disabled_by.or_else(|| {
+                let rule = violation.rule.as_ref()?;
+                disabled_rules.get(rule.code).copied()
+            })
+        }
+
+        masked_by_line_rules(self, violation).or_else(|| masked_by_range_rules(self, violation))
+    }
+
+    /// unused_directives returns the directives which do not mask any of the violations, out of
+    /// those whose rules all ran.
+    fn unused_directives(
+        &self,
+        violations: &[SQLBaseError],
+        ran_rules: &HashSet<&str>,
+        available_rules: &HashSet<&str>,
+    ) -> Vec<&NoQADirective> {
+        let used: HashSet<usize> = violations
+            .iter()
+            .filter_map(|violation| self.masked_by(violation))
+            .collect();
+
+        self.ignore_list
+            .iter()
+            .enumerate()
+            .filter(|(idx, ignore)| {
+                !used.contains(idx)
+                    && !ignore.is_enable()
+                    && ignore.is_checked(ran_rules, available_rules)
+            })
+            .map(|(_, ignore)| ignore)
+            .collect()
+    }
+
+    /// Report the directives which do not mask any of the violations, or which refer to rules
+    /// that are not in the available rule set. Directives for rules which didn't run, e.g.
+    /// because of the `rules` setting, are never reported as unused.
+    pub fn unused_violations(
+        &self,
+        violations: &[SQLBaseError],
+        ran_rules: &HashSet<&str>,
+        available_rules: &HashSet<&str>,
+    ) -> Vec<SQLBaseError> {
+        let unused = self.unused_directives(violations, ran_rules, available_rules);
+
+        self.ignore_list
+            .iter()
+            .filter_map(|ignore| {
+                let is_unused = unused.iter().any(|unused| std::ptr::eq(*unused, ignore));
+                let unknown_rules = ignore.unknown_rules(available_rules);
+
+                let description = if !unknown_rules.is_empty() {
+                    format!(
+                        "Unknown rule {} in noqa: {:?}",
+                        unknown_rules.join(", "),
+                        ignore.raw_string()
+                    )
+                } else if is_unused {
+                    format!("Unused noqa: {:?}", ignore.raw_string())
+                } else {
+                    return None;
+                };
+
+                Some(SQLBaseError {
+                    fatal: false,
+                    ignore: false,
+                    warning: true,
+                    line_no: ignore.line_no(),
+                    line_pos: ignore.line_pos(),
+                    description,
+                    rule: Some(ErrorStructRule {
+                        name: "noqa",
+                        code: "NOQA",
+                    }),
+                    source_slice: Default::default(),
+                    fixable: is_unused && ignore.is_whole_comment(),
+                    fixes: Vec::new(),
+                })
+            })
+            .collect()
+    }
+
+    /// Fixes which remove the comments of directives that do not mask any of the violations.
+    /// Comments which hold other text besides the directive are left alone.
+    pub fn unused_fixes(
+        &self,
+        tree: &ErasedSegment,
+        violations: &[SQLBaseError],
+        ran_rules: &HashSet<&str>,
+        available_rules: &HashSet<&str>,
+    ) -> Vec<LintFix> {
+        let unused = self
+            .unused_directives(violations, ran_rules, available_rules)
+            .into_iter()
+            .filter(|ignore| ignore.is_whole_comment())
+            .collect_vec();
+        if unused.is_empty() {
+            return Vec::new();
+        }
+
+        let raw_segments = tree
+            .get_raw_segments()
+            .into_iter()
+            .filter(|segment| !segment.is_meta())
+            .collect_vec();
+
+        let mut fixes = Vec::new();
+        for (idx, segment) in raw_segments.iter().enumerate() {
+            if !matches!(
+                segment.get_type(),
+                SyntaxKind::Comment | SyntaxKind::InlineComment | SyntaxKind::BlockComment
+            ) {
+                continue;
+            }
+
+            let Ok(Some(ignore)) = IgnoreMask::extract_ignore_from_comment(segment.clone()) else {
+                continue;
+            };
+            if !unused.contains(&&ignore) {
+                continue;
+            }
+
+            fixes.push(LintFix::delete(segment.clone()));
+
+            // Remove the whitespace before the comment, and the newline after it when the comment
+            // is on a line of its own.
+            let mut start = idx;
+            if start > 0 && raw_segments[start - 1].is_type(SyntaxKind::Whitespace) {
+                start -= 1;
+                fixes.push(LintFix::delete(raw_segments[start].clone()));
             }
 
-            false
+            if start == 0 || raw_segments[start - 1].is_type(SyntaxKind::Newline) {
+                if let Some(newline) = raw_segments
+                    .get(idx + 1)
+                    .filter(|segment| segment.is_type(SyntaxKind::Newline))
+                {
+                    fixes.push(LintFix::delete(newline.clone()));
+                }
+            }
         }
 
-        is_masked_by_line_rules(self, violation) || is_masked_by_range_rules(self, violation)
+        fixes
     }
 }
 
@@ -492,6 +696,7 @@ mod tests {
             }),
             source_slice: Default::default(),
             fixable: true,
+            fixes: Vec::new(),
         };
         let mask = IgnoreMask {
             ignore_list: vec![NoQADirective::LineIgnoreRules(LineIgnoreRules {
@@ -689,6 +894,72 @@ FROM foo
         assert_eq!(result_with_disabled.get_violations(None).len(), 2);
     }
 
+    #[test]
+    fn test_linter_unused_noqa() {
+        let linter = Linter::new(
+            FluffConfig::from_source(
+                r#"
+[sqruff]
+dialect = bigquery
+rules = AL02
+warn_unused_ignores = True
+    "#,
+            ),
+            None,
+            None,
+        );
+
+        let sql = r#"SELECT
+    col_a a, --noqa: AL02
+    col_b AS b --noqa: AL02
+FROM foo -- noqa: XX01
+"#;
+
+        let result = linter.lint_string(sql, None, false);
+        let violations = result.get_violations(None);
+
+        assert_eq!(
+            violations
+                .iter()
+                .map(|v| (v.line_no, v.rule_code(), v.description.as_str()))
+                .collect_vec(),
+            [
+                (3, "NOQA", r#"Unused noqa: "--noqa: AL02""#),
+                (4, "NOQA", r#"Unknown rule XX01 in noqa: "-- noqa: XX01""#),
+            ]
+        );
+
+        let fixed = linter.lint_string(sql, None, true).fix_string();
+        assert!(
+            fixed.contains("--noqa: AL02\n    col_b AS b\nFROM foo\n"),
+            "{fixed}"
+        );
+    }
+
+    #[test]
+    fn test_linter_noqa_for_rules_which_did_not_run() {
+        let linter = Linter::new(
+            FluffConfig::from_source(
+                r#"
+[sqruff]
+dialect = bigquery
+rules = LT01
+warn_unused_ignores = True
+    "#,
+            ),
+            None,
+            None,
+        );
+
+        let sql = "SELECT col_a a -- noqa: AL02\nFROM foo -- noqa\n";
+
+        let result = linter.lint_string(sql, None, false);
+        assert_eq!(result.get_violations(None), []);
+
+        let fixed = linter.lint_string(sql, None, true).fix_string();
+        assert_eq!(fixed, sql);
+    }
+
     #[test]
     fn test_range_code() {
         let linter_without_disabled = Linter::new(
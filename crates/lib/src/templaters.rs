@@ -5,12 +5,16 @@ use sqruff_lib_core::templaters::base::TemplatedFile;
 
 use crate::cli::formatters::Formatter;
 use crate::core::config::FluffConfig;
+use crate::templaters::dbt::DBTTemplater;
+use crate::templaters::jinja::JinjaTemplater;
 use crate::templaters::placeholder::PlaceholderTemplater;
 use crate::templaters::raw::RawTemplater;
 
 #[cfg(feature = "python")]
 use crate::templaters::python::PythonTemplater;
 
+pub mod dbt;
+pub mod jinja;
 pub mod placeholder;
 #[cfg(feature = "python")]
 pub mod python;
@@ -18,16 +22,28 @@ pub mod raw;
 
 pub static RAW_TEMPLATER: RawTemplater = RawTemplater;
 pub static PLACEHOLDER_TEMPLATER: PlaceholderTemplater = PlaceholderTemplater;
+pub static JINJA_TEMPLATER: JinjaTemplater = JinjaTemplater;
+pub static DBT_TEMPLATER: DBTTemplater = DBTTemplater::new();
 #[cfg(feature = "python")]
 pub static PYTHON_TEMPLATER: PythonTemplater = PythonTemplater;
 
 // templaters returns all the templaters that are available in the library
 #[cfg(feature = "python")]
-pub static TEMPLATERS: [&'static dyn Templater; 3] =
-    [&RAW_TEMPLATER, &PLACEHOLDER_TEMPLATER, &PYTHON_TEMPLATER];
+pub static TEMPLATERS: [&'static dyn Templater; 5] = [
+    &RAW_TEMPLATER,
+    &PLACEHOLDER_TEMPLATER,
+    &JINJA_TEMPLATER,
+    &DBT_TEMPLATER,
+    &PYTHON_TEMPLATER,
+];
 
 #[cfg(not(feature = "python"))]
-pub static TEMPLATERS: [&'static dyn Templater; 2] = [&RAW_TEMPLATER, &PLACEHOLDER_TEMPLATER];
+pub static TEMPLATERS: [&'static dyn Templater; 4] = [
+    &RAW_TEMPLATER,
+    &PLACEHOLDER_TEMPLATER,
+    &JINJA_TEMPLATER,
+    &DBT_TEMPLATER,
+];
 
 pub trait Templater: Send + Sync {
     /// The name of the templater.
@@ -0,0 +1,357 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use minijinja::value::{Kwargs, Rest};
+use minijinja::{Environment, Value as JinjaValue};
+use sqruff_lib_core::errors::SQLFluffUserError;
+use sqruff_lib_core::templaters::base::TemplatedFile;
+
+use crate::cli::formatters::Formatter;
+use crate::core::config::{FluffConfig, Value};
+use crate::templaters::jinja::{context_from_config, environment, render_template, MacroLibrary};
+use crate::templaters::Templater;
+
+const DBT_PROJECT_FILE: &str = "dbt_project.yml";
+
+#[derive(Default)]
+pub struct DBTTemplater {
+    /// Projects which have already been loaded, keyed by their directory.
+    projects: Mutex<BTreeMap<PathBuf, Arc<DbtProject>>>,
+}
+
+impl DBTTemplater {
+    pub const fn new() -> Self {
+        Self {
+            projects: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Load the project in a directory, reusing it if it was loaded before.
+    fn project(&self, project_dir: PathBuf) -> Result<Arc<DbtProject>, SQLFluffUserError> {
+        if let Some(project) = self.projects.lock().unwrap().get(&project_dir) {
+            return Ok(project.clone());
+        }
+
+        let project = Arc::new(DbtProject::load(&project_dir)?);
+        self.projects
+            .lock()
+            .unwrap()
+            .insert(project_dir, project.clone());
+        Ok(project)
+    }
+}
+
+impl Templater for DBTTemplater {
+    fn name(&self) -> &'static str {
+        "dbt"
+    }
+
+    fn description(&self) -> &'static str {
+        r#"The dbt templater renders dbt models natively, without running dbt or connecting to a warehouse. It builds on the [jinja](#jinja) templater and reads the `dbt_project.yml` of the project to load its macros and variables.
+
+dbt templating can be enabled in the config using:
+
+```ini
+[sqruff]
+templater = dbt
+```
+
+The project is found by searching upwards from each model for a `dbt_project.yml`. It can also be set explicitly, relative to the config file which declares it:
+
+```ini
+[sqruff:templater:dbt]
+project_dir = path/to/project
+```
+
+All macros in the project's `macro-paths` (by default `macros`) are available to models. The dbt builtins are resolved as follows:
+
+- `ref('model')` and `ref('package', 'model')` render as the name of the model.
+- `source('source_name', 'table')` renders as `source_name.table`.
+- `var('name', default)` renders the variable from the `vars` section of `dbt_project.yml` or the `vars` section below, falling back to the default and otherwise the name of the variable.
+- `config(...)` renders as nothing, but `is_incremental()` is true when the model is configured with `materialized='incremental'`.
+- `env_var('NAME', default)` reads from the environment.
+- `this` renders as the name of the model being linted.
+
+Variables can be overridden, and extra context provided, in the config:
+
+```ini
+[sqruff:templater:dbt:vars]
+start_date = 2024-01-01
+
+[sqruff:templater:dbt:context]
+my_variable = 1
+```"#
+    }
+
+    fn process(
+        &self,
+        in_str: &str,
+        f_name: &str,
+        config: Option<&FluffConfig>,
+        _formatter: &Option<Arc<dyn Formatter>>,
+    ) -> Result<TemplatedFile, SQLFluffUserError> {
+        let dbt_config = config.and_then(|config| config.get("dbt", "templater").as_map());
+
+        let project_dir = match dbt_config
+            .and_then(|config| config.get("project_dir"))
+            .and_then(Value::as_string)
+        {
+            Some(project_dir) => PathBuf::from(project_dir),
+            None => find_project_dir(Path::new(f_name)).ok_or_else(|| {
+                SQLFluffUserError::new(format!(
+                    "Failed to find a {DBT_PROJECT_FILE} for {f_name}. Set `project_dir` in the \
+                     [sqruff:templater:dbt] section of the config."
+                ))
+            })?,
+        };
+        let project = self.project(project_dir)?;
+
+        let mut vars = project.vars.clone();
+        if let Some(overrides) = dbt_config
+            .and_then(|config| config.get("vars"))
+            .and_then(Value::as_map)
+        {
+            vars.extend(context_from_config(overrides));
+        }
+
+        let model_name = Path::new(f_name)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let mut env = environment();
+        add_dbt_builtins(&mut env, vars, model_name);
+
+        let context = dbt_config
+            .and_then(|config| config.get("context"))
+            .and_then(Value::as_map)
+            .map(context_from_config)
+            .unwrap_or_default();
+
+        render_template(&env, in_str, f_name, context, &project.macros)
+    }
+}
+
+/// The parts of `dbt_project.yml` which are needed to render models.
+#[derive(Debug, Default)]
+struct DbtProject {
+    macro_paths: Vec<String>,
+    vars: BTreeMap<String, JinjaValue>,
+    macros: MacroLibrary,
+}
+
+impl DbtProject {
+    fn load(project_dir: &Path) -> Result<Self, SQLFluffUserError> {
+        let path = project_dir.join(DBT_PROJECT_FILE);
+        let source = std::fs::read_to_string(&path).map_err(|err| {
+            SQLFluffUserError::new(format!("Failed to read {}: {err}", path.display()))
+        })?;
+        let mut project = Self::from_source(&source).map_err(|err| {
+            SQLFluffUserError::new(format!("Failed to parse {}: {err}", path.display()))
+        })?;
+
+        for macro_path in &project.macro_paths {
+            let macro_path = project_dir.join(macro_path);
+            if macro_path.exists() {
+                project.macros.load_path(&macro_path)?;
+            }
+        }
+
+        Ok(project)
+    }
+
+    fn from_source(source: &str) -> Result<Self, serde_yaml::Error> {
+        let project: serde_yaml::Value = serde_yaml::from_str(source)?;
+
+        let macro_paths = match project.get("macro-paths") {
+            Some(serde_yaml::Value::Sequence(paths)) => paths
+                .iter()
+                .filter_map(serde_yaml::Value::as_str)
+                .map(ToOwned::to_owned)
+                .collect(),
+            _ => vec!["macros".to_owned()],
+        };
+
+        // Variables may be scoped to a package, in which case they are nested
+        // under its name. Those scoped to this project take precedence.
+        let project_name = project.get("name").and_then(serde_yaml::Value::as_str);
+        let mut vars = BTreeMap::new();
+        let mut scoped = BTreeMap::new();
+        if let Some(serde_yaml::Value::Mapping(mapping)) = project.get("vars") {
+            for (key, value) in mapping {
+                let Some(key) = key.as_str() else {
+                    continue;
+                };
+                match value {
+                    serde_yaml::Value::Mapping(package_vars) if Some(key) == project_name => {
+                        for (key, value) in package_vars {
+                            if let Some(key) = key.as_str() {
+                                scoped.insert(key.to_owned(), JinjaValue::from_serialize(value));
+                            }
+                        }
+                    }
+                    _ => {
+                        vars.insert(key.to_owned(), JinjaValue::from_serialize(value));
+                    }
+                }
+            }
+        }
+        vars.extend(scoped);
+
+        Ok(Self {
+            macro_paths,
+            vars,
+            macros: MacroLibrary::default(),
+        })
+    }
+}
+
+/// Search upwards from a model for the directory containing `dbt_project.yml`.
+fn find_project_dir(f_name: &Path) -> Option<PathBuf> {
+    let f_name = std::path::absolute(f_name).ok()?;
+    f_name
+        .ancestors()
+        .skip(1)
+        .find(|dir| dir.join(DBT_PROJECT_FILE).is_file())
+        .map(Path::to_path_buf)
+}
+
+/// Add implementations of the dbt builtins which can be resolved without a
+/// connection to the warehouse.
+fn add_dbt_builtins(
+    env: &mut Environment<'static>,
+    vars: BTreeMap<String, JinjaValue>,
+    model_name: String,
+) {
+    let incremental = Arc::new(AtomicBool::new(false));
+
+    env.add_function("ref", |args: Rest<JinjaValue>, _kwargs: Kwargs| {
+        args.last().map(ToString::to_string).unwrap_or_default()
+    });
+    env.add_function("source", |source_name: String, table: String| {
+        format!("{source_name}.{table}")
+    });
+    env.add_function(
+        "var",
+        move |name: String, default: Option<JinjaValue>| match vars.get(&name) {
+            Some(value) => value.clone(),
+            None => default.unwrap_or_else(|| JinjaValue::from(name)),
+        },
+    );
+    env.add_function("config", {
+        let incremental = incremental.clone();
+        move |_args: Rest<JinjaValue>, kwargs: Kwargs| {
+            if let Ok(Some(materialized)) = kwargs.get::<Option<String>>("materialized") {
+                incremental.store(materialized == "incremental", Ordering::Relaxed);
+            }
+            ""
+        }
+    });
+    env.add_function("is_incremental", move || {
+        incremental.load(Ordering::Relaxed)
+    });
+    env.add_function("env_var", |name: String, default: Option<String>| {
+        std::env::var(&name).ok().or(default).unwrap_or(name)
+    });
+    env.add_global("this", model_name);
+}
+
+#[cfg(test)]
+mod tests {
+    use sqruff_lib_core::templaters::base::TemplatedFileSlice;
+
+    use super::*;
+
+    fn project() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(DBT_PROJECT_FILE),
+            "name: shop\n\
+             macro-paths: [\"macros\"]\n\
+             vars:\n  \
+               currency: EUR\n  \
+               shop:\n    \
+                 start_date: '2024-01-01'\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(dir.path().join("macros")).unwrap();
+        std::fs::write(
+            dir.path().join("macros/cents.sql"),
+            "{% macro cents(col) %}({{ col }} / 100){% endmacro %}",
+        )
+        .unwrap();
+        std::fs::create_dir_all(dir.path().join("models")).unwrap();
+        dir
+    }
+
+    fn render(dir: &Path, in_str: &str) -> TemplatedFile {
+        let f_name = dir.join("models/orders.sql");
+        DBTTemplater::default()
+            .process(in_str, &f_name.to_string_lossy(), None, &None)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_templater_dbt_builtins() {
+        let dir = project();
+        let templated_file = render(
+            dir.path(),
+            "SELECT {{ cents('amount') }}, '{{ var(\"currency\") }}', '{{ var(\"start_date\") }}'\n\
+             FROM {{ ref('stg_orders') }} JOIN {{ source('raw', 'customers') }}",
+        );
+
+        assert_eq!(
+            templated_file.templated(),
+            "SELECT (amount / 100), 'EUR', '2024-01-01'\nFROM stg_orders JOIN raw.customers"
+        );
+    }
+
+    #[test]
+    fn test_templater_dbt_is_incremental() {
+        let dir = project();
+        let in_str = "{{ config(materialized='incremental') }}\n\
+                      SELECT * FROM {{ this }}\n\
+                      {% if is_incremental() %}WHERE 1 = 1{% endif %}\n";
+
+        let templated_file = render(dir.path(), in_str);
+        assert_eq!(
+            templated_file.templated(),
+            "\nSELECT * FROM orders\nWHERE 1 = 1\n"
+        );
+        assert_eq!(
+            templated_file.sliced_file[0],
+            TemplatedFileSlice::new("templated", 0..40, 0..0)
+        );
+
+        let templated_file = render(dir.path(), &in_str.replace("incremental'", "table'"));
+        assert_eq!(templated_file.templated(), "\nSELECT * FROM orders\n\n");
+    }
+
+    #[test]
+    fn test_templater_dbt_project_is_loaded_once() {
+        let dir = project();
+        let templater = DBTTemplater::default();
+        let f_name = dir.path().join("models/orders.sql");
+        let f_name = f_name.to_string_lossy();
+
+        templater.process("SELECT 1", &f_name, None, &None).unwrap();
+        std::fs::remove_file(dir.path().join("macros/cents.sql")).unwrap();
+
+        let templated_file = templater
+            .process("SELECT {{ cents('amount') }}", &f_name, None, &None)
+            .unwrap();
+        assert_eq!(templated_file.templated(), "SELECT (amount / 100)");
+    }
+
+    #[test]
+    fn test_templater_dbt_missing_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let f_name = dir.path().join("model.sql");
+
+        assert!(DBTTemplater::default()
+            .process("SELECT 1", &f_name.to_string_lossy(), None, &None)
+            .is_err());
+    }
+}
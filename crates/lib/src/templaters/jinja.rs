@@ -0,0 +1,1109 @@
+use std::collections::{BTreeMap, HashSet};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+
+use ahash::AHashMap;
+use minijinja::value::{Kwargs, Rest};
+use minijinja::{Environment, Value as JinjaValue};
+use sqruff_lib_core::errors::SQLFluffUserError;
+use sqruff_lib_core::templaters::base::{RawFileSlice, TemplatedFile, TemplatedFileSlice};
+
+use crate::cli::formatters::Formatter;
+use crate::core::config::{FluffConfig, Value};
+use crate::templaters::Templater;
+
+#[derive(Default)]
+pub struct JinjaTemplater;
+
+impl Templater for JinjaTemplater {
+    fn name(&self) -> &'static str {
+        "jinja"
+    }
+
+    fn description(&self) -> &'static str {
+        r#"The Jinja templater renders Jinja SQL natively in Rust, without requiring Python or sqlfluff to be installed. It supports expressions (`{{ }}`), comments (`{# #}`), `{% if %}`/`{% elif %}`/`{% else %}`, `{% for %}` loops, `{% set %}` and `{% macro %}` definitions, as well as whitespace control with `-`.
+
+Jinja templating can be enabled in the config using:
+
+```ini
+[sqruff]
+templater = jinja
+```
+
+Variables are provided in the `context` section:
+
+```ini
+[sqruff:templater:jinja:context]
+my_schema = analytics
+my_columns = ['id', 'name']
+```
+
+Values which look like lists or dictionaries are evaluated as Jinja literals. Variables which are not defined in the context are rendered as their own name, so `{{ foo }}` renders as `foo`.
+
+Macros defined in the file being linted can be used directly. Macros stored in other files can be loaded with `load_macros_from_path`, a comma separated list of files or directories (searched recursively for `.sql` files), relative to the config file which declares it:
+
+```ini
+[sqruff:templater:jinja]
+load_macros_from_path = macros
+```
+
+By default the templater also provides placeholder implementations of the most common dbt builtins (`ref`, `source`, `config`, `var`, `is_incremental` and `this`), so that dbt models can be linted without a dbt project. This can be disabled with:
+
+```ini
+[sqruff:templater:jinja]
+apply_dbt_builtins = False
+```"#
+    }
+
+    fn process(
+        &self,
+        in_str: &str,
+        f_name: &str,
+        config: Option<&FluffConfig>,
+        _formatter: &Option<Arc<dyn Formatter>>,
+    ) -> Result<TemplatedFile, SQLFluffUserError> {
+        let jinja_config = config.and_then(|config| config.get("jinja", "templater").as_map());
+
+        let mut env = environment();
+        let apply_dbt_builtins = jinja_config
+            .and_then(|config| config.get("apply_dbt_builtins"))
+            .and_then(Value::as_bool)
+            .unwrap_or(true);
+        if apply_dbt_builtins {
+            add_dbt_builtins(&mut env);
+        }
+
+        let context = jinja_config
+            .and_then(|config| config.get("context"))
+            .and_then(Value::as_map)
+            .map(context_from_config)
+            .unwrap_or_default();
+
+        let mut macros = MacroLibrary::default();
+        if let Some(paths) = jinja_config
+            .and_then(|config| config.get("load_macros_from_path"))
+            .and_then(Value::as_string)
+        {
+            for path in paths
+                .split(',')
+                .map(str::trim)
+                .filter(|path| !path.is_empty())
+            {
+                macros.load_path(Path::new(path))?;
+            }
+        }
+
+        render_template(&env, in_str, f_name, context, &macros)
+    }
+}
+
+/// Create the Jinja environment used to render templates.
+pub(crate) fn environment() -> Environment<'static> {
+    let mut env = Environment::new();
+    // Rendered snippets are spliced back into the file, so trailing newlines
+    // produced by macros must be preserved.
+    env.set_keep_trailing_newline(true);
+    env
+}
+
+/// Add placeholder implementations of the dbt builtins, matching the ones
+/// provided by sqlfluff's jinja templater.
+fn add_dbt_builtins(env: &mut Environment<'static>) {
+    env.add_function("ref", |args: Rest<JinjaValue>, _kwargs: Kwargs| {
+        args.last().map(ToString::to_string).unwrap_or_default()
+    });
+    env.add_function("source", |source_name: String, table: String| {
+        format!("{source_name}_{table}")
+    });
+    env.add_function("config", |_args: Rest<JinjaValue>, _kwargs: Kwargs| "");
+    env.add_function("var", |_args: Rest<JinjaValue>, _kwargs: Kwargs| "item");
+    env.add_function("is_incremental", || false);
+    env.add_global("this", "this_model");
+}
+
+/// Convert the `context` section of the config into Jinja variables.
+pub(crate) fn context_from_config(
+    context: &AHashMap<String, Value>,
+) -> BTreeMap<String, JinjaValue> {
+    context
+        .iter()
+        .map(|(key, value)| (key.clone(), to_jinja_value(value)))
+        .collect()
+}
+
+fn to_jinja_value(value: &Value) -> JinjaValue {
+    match value {
+        Value::Int(value) => JinjaValue::from(*value),
+        Value::Bool(value) => JinjaValue::from(*value),
+        Value::Float(value) => JinjaValue::from(*value),
+        Value::String(value) => {
+            // Lists, dicts and tuples are written as literals in the config.
+            if value.starts_with(['[', '{', '(']) {
+                if let Ok(literal) = Environment::new()
+                    .compile_expression(value)
+                    .and_then(|expr| expr.eval(()))
+                {
+                    return literal;
+                }
+            }
+            JinjaValue::from(value.as_ref())
+        }
+        Value::Map(map) => map
+            .iter()
+            .map(|(key, value)| (key.clone(), to_jinja_value(value)))
+            .collect(),
+        Value::Array(values) => values.iter().map(to_jinja_value).collect(),
+        Value::None => JinjaValue::from(()),
+    }
+}
+
+/// Macro definitions which are made available when rendering expressions.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct MacroLibrary {
+    /// The concatenated source of every `{% macro %}` block.
+    source: String,
+    names: Vec<String>,
+}
+
+impl MacroLibrary {
+    /// Load all macros from a file, or from all `.sql` files within a directory.
+    pub(crate) fn load_path(&mut self, path: &Path) -> Result<(), SQLFluffUserError> {
+        if path.is_dir() {
+            let mut files = walkdir::WalkDir::new(path)
+                .into_iter()
+                .filter_map(Result::ok)
+                .filter(|entry| {
+                    entry.file_type().is_file()
+                        && entry.path().extension().is_some_and(|ext| ext == "sql")
+                })
+                .map(|entry| entry.into_path())
+                .collect::<Vec<_>>();
+            files.sort();
+
+            for file in files {
+                self.load_file(&file)?;
+            }
+            Ok(())
+        } else {
+            self.load_file(path)
+        }
+    }
+
+    fn load_file(&mut self, path: &Path) -> Result<(), SQLFluffUserError> {
+        let source = std::fs::read_to_string(path).map_err(|err| {
+            SQLFluffUserError::new(format!(
+                "Failed to load macros from {}: {err}",
+                path.display()
+            ))
+        })?;
+        self.load_str(&source).map_err(|err| {
+            SQLFluffUserError::new(format!(
+                "Failed to load macros from {}: {err}",
+                path.display()
+            ))
+        })
+    }
+
+    /// Extract the macro definitions from a template, ignoring everything else.
+    pub(crate) fn load_str(&mut self, source: &str) -> Result<(), String> {
+        let tokens = tokenize(source)?;
+        let nodes = TemplateParser::new(source, &tokens).parse()?;
+        self.collect(source, &tokens, &nodes);
+        Ok(())
+    }
+
+    fn collect(&mut self, source: &str, tokens: &[Token], nodes: &[Node]) {
+        for node in nodes {
+            if let Node::Macro { start, end } = *node {
+                self.source
+                    .push_str(&source[tokens[start].tag.start..tokens[end].tag.end]);
+                let name = tokens[start].content(source)["macro".len()..].trim_start();
+                let name = name.split('(').next().unwrap_or_default().trim();
+                self.names.push(name.to_owned());
+            }
+        }
+    }
+}
+
+/// The name under which the macro library is added to the environment.
+const MACROS_TEMPLATE: &str = "__macros__";
+
+/// Render a Jinja template, tracking which parts of the output come from
+/// which parts of the source.
+pub(crate) fn render_template(
+    env: &Environment<'_>,
+    in_str: &str,
+    f_name: &str,
+    context: BTreeMap<String, JinjaValue>,
+    library: &MacroLibrary,
+) -> Result<TemplatedFile, SQLFluffUserError> {
+    let error = |err: String| {
+        SQLFluffUserError::new(format!("Failed to render {f_name} with jinja: {err}"))
+    };
+
+    let tokens = tokenize(in_str).map_err(error)?;
+    let nodes = TemplateParser::new(in_str, &tokens)
+        .parse()
+        .map_err(error)?;
+
+    let mut macros = library.clone();
+    macros.collect(in_str, &tokens, &nodes);
+
+    // The macros are compiled once here, and imported by the snippets which
+    // use them.
+    let mut env = env.clone();
+    env.add_template(MACROS_TEMPLATE, &macros.source)
+        .map_err(|err| error(err.to_string()))?;
+
+    let mut renderer = Renderer {
+        env: &env,
+        source: in_str,
+        tokens: &tokens,
+        macros: &macros,
+        scopes: vec![context],
+        templated: String::new(),
+        sliced: Vec::new(),
+    };
+    renderer.render_nodes(&nodes).map_err(error)?;
+
+    let raw_sliced = raw_slices(in_str, &tokens);
+    TemplatedFile::new(
+        in_str.to_string(),
+        f_name.to_string(),
+        Some(renderer.templated),
+        Some(renderer.sliced),
+        Some(raw_sliced),
+    )
+    .map_err(|err| SQLFluffUserError::new(format!("{err:?}")))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    Literal,
+    Expression,
+    Statement,
+    Comment,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    /// The span in the source, including any whitespace removed by `-`
+    /// modifiers.
+    span: Range<usize>,
+    /// The span of the tag itself, from the opening to the closing delimiter.
+    tag: Range<usize>,
+    /// The span of the trimmed contents of the tag.
+    content: Range<usize>,
+}
+
+impl Token {
+    fn literal(span: Range<usize>) -> Self {
+        Token {
+            kind: TokenKind::Literal,
+            span: span.clone(),
+            tag: span.clone(),
+            content: span,
+        }
+    }
+
+    fn content<'a>(&self, source: &'a str) -> &'a str {
+        &source[self.content.clone()]
+    }
+
+    fn keyword<'a>(&self, source: &'a str) -> &'a str {
+        if self.kind != TokenKind::Statement {
+            return "";
+        }
+        let content = self.content(source);
+        let end = content
+            .find(|ch: char| !(ch.is_alphanumeric() || ch == '_'))
+            .unwrap_or(content.len());
+        &content[..end]
+    }
+
+    /// The slice type, using the same names as sqlfluff.
+    fn slice_type(&self, source: &str) -> &'static str {
+        match self.kind {
+            TokenKind::Literal => "literal",
+            TokenKind::Expression => "templated",
+            TokenKind::Comment => "comment",
+            TokenKind::Statement => match self.keyword(source) {
+                "set" if self.content(source).contains('=') => "templated",
+                "if" | "for" | "macro" | "call" | "filter" | "raw" | "set" => "block_start",
+                "elif" | "else" => "block_mid",
+                keyword if keyword.starts_with("end") => "block_end",
+                _ => "templated",
+            },
+        }
+    }
+}
+
+/// Split a template into literals and tags.
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut literal_start = 0;
+    let mut pos = 0;
+
+    while let Some(offset) = source[pos..].find('{') {
+        let start = pos + offset;
+        let kind = match bytes.get(start + 1) {
+            Some(b'{') => TokenKind::Expression,
+            Some(b'%') => TokenKind::Statement,
+            Some(b'#') => TokenKind::Comment,
+            _ => {
+                pos = start + 1;
+                continue;
+            }
+        };
+
+        let close = find_tag_end(bytes, start + 2, kind)
+            .ok_or_else(|| format!("unclosed tag starting at position {start}"))?;
+        let tag = start..close + 2;
+
+        let mut content = start + 2..close;
+        let strip_left = bytes.get(content.start) == Some(&b'-');
+        if content.start < content.end && matches!(bytes[content.start], b'-' | b'+') {
+            content.start += 1;
+        }
+        let strip_right = content.start < content.end && bytes[content.end - 1] == b'-';
+        if content.start < content.end && matches!(bytes[content.end - 1], b'-' | b'+') {
+            content.end -= 1;
+        }
+        let inner = &source[content.clone()];
+        let content = content.start + (inner.len() - inner.trim_start().len())
+            ..content.end - (inner.len() - inner.trim_end().len());
+
+        let mut span = tag.clone();
+        if strip_left {
+            span.start = literal_start + source[literal_start..start].trim_end().len();
+        }
+        if strip_right {
+            let rest = &source[tag.end..];
+            span.end += rest.len() - rest.trim_start().len();
+        }
+
+        if span.start > literal_start {
+            tokens.push(Token::literal(literal_start..span.start));
+        }
+        let token = Token {
+            kind,
+            span: span.clone(),
+            tag,
+            content,
+        };
+
+        pos = span.end;
+        literal_start = span.end;
+
+        // The contents of a raw block are literal, so skip straight to the end
+        // of the block.
+        if token.keyword(source) == "raw" {
+            let end_raw = lazy_regex::regex!(r"\{%[-+]?\s*endraw\s*[-+]?%\}");
+            let end = end_raw
+                .find(&source[pos..])
+                .ok_or_else(|| format!("unclosed raw block at position {start}"))?;
+            pos += end.start();
+        }
+
+        tokens.push(token);
+    }
+
+    if literal_start < source.len() {
+        tokens.push(Token::literal(literal_start..source.len()));
+    }
+
+    Ok(tokens)
+}
+
+/// Find the position of the closing delimiter of a tag, skipping over any
+/// quoted strings.
+fn find_tag_end(bytes: &[u8], from: usize, kind: TokenKind) -> Option<usize> {
+    let closing: &[u8] = match kind {
+        TokenKind::Comment => {
+            return bytes[from..]
+                .windows(2)
+                .position(|window| window == b"#}")
+                .map(|pos| from + pos);
+        }
+        TokenKind::Expression => b"}}",
+        TokenKind::Statement => b"%}",
+        TokenKind::Literal => unreachable!(),
+    };
+
+    let mut quote = None;
+    let mut idx = from;
+    while idx < bytes.len() {
+        let byte = bytes[idx];
+        match quote {
+            Some(_) if byte == b'\\' => idx += 1,
+            Some(q) if byte == q => quote = None,
+            Some(_) => {}
+            None if byte == b'\'' || byte == b'"' => quote = Some(byte),
+            None if bytes[idx..].starts_with(closing) => return Some(idx),
+            None => {}
+        }
+        idx += 1;
+    }
+    None
+}
+
+fn raw_slices(source: &str, tokens: &[Token]) -> Vec<RawFileSlice> {
+    let mut block_idx = 0;
+    tokens
+        .iter()
+        .map(|token| {
+            let slice_type = token.slice_type(source);
+            if matches!(slice_type, "block_start" | "block_end") {
+                block_idx += 1;
+            }
+            RawFileSlice::new(
+                source[token.span.clone()].to_string(),
+                slice_type.to_string(),
+                token.span.start,
+                None,
+                Some(block_idx),
+            )
+        })
+        .collect()
+}
+
+#[derive(Debug)]
+enum Node {
+    /// A literal, an expression, a comment or a standalone statement.
+    Leaf(usize),
+    If {
+        branches: Vec<(usize, Vec<Node>)>,
+        end: usize,
+    },
+    For {
+        start: usize,
+        body: Vec<Node>,
+        otherwise: Option<(usize, Vec<Node>)>,
+        end: usize,
+    },
+    SetBlock {
+        start: usize,
+        body: Vec<Node>,
+        end: usize,
+    },
+    Macro {
+        start: usize,
+        end: usize,
+    },
+    Raw {
+        start: usize,
+        body: Vec<Node>,
+        end: usize,
+    },
+}
+
+struct TemplateParser<'a> {
+    source: &'a str,
+    tokens: &'a [Token],
+    idx: usize,
+}
+
+impl<'a> TemplateParser<'a> {
+    fn new(source: &'a str, tokens: &'a [Token]) -> Self {
+        TemplateParser {
+            source,
+            tokens,
+            idx: 0,
+        }
+    }
+
+    fn parse(mut self) -> Result<Vec<Node>, String> {
+        let (nodes, end) = self.parse_nodes(&[])?;
+        match end {
+            Some(end) => Err(self.unexpected(end)),
+            None => Ok(nodes),
+        }
+    }
+
+    fn unexpected(&self, idx: usize) -> String {
+        format!(
+            "unexpected tag '{}' at position {}",
+            self.tokens[idx].content(self.source),
+            self.tokens[idx].tag.start
+        )
+    }
+
+    /// Parse nodes until one of the `until` statements (or the end of the file)
+    /// is reached, returning the index of the terminating statement.
+    fn parse_nodes(&mut self, until: &[&str]) -> Result<(Vec<Node>, Option<usize>), String> {
+        let mut nodes = Vec::new();
+
+        while self.idx < self.tokens.len() {
+            let idx = self.idx;
+            self.idx += 1;
+
+            let token = &self.tokens[idx];
+            if token.kind != TokenKind::Statement {
+                nodes.push(Node::Leaf(idx));
+                continue;
+            }
+
+            let keyword = token.keyword(self.source);
+            if until.contains(&keyword) {
+                return Ok((nodes, Some(idx)));
+            }
+
+            let node = match keyword {
+                "if" => {
+                    let mut branches = Vec::new();
+                    let mut tag = idx;
+                    loop {
+                        let (body, next) = self.parse_nodes(&["elif", "else", "endif"])?;
+                        branches.push((tag, body));
+                        let next = next.ok_or_else(|| self.unclosed(idx))?;
+                        if self.tokens[next].keyword(self.source) == "endif" {
+                            break Node::If {
+                                branches,
+                                end: next,
+                            };
+                        }
+                        tag = next;
+                    }
+                }
+                "for" => {
+                    let (body, next) = self.parse_nodes(&["else", "endfor"])?;
+                    let next = next.ok_or_else(|| self.unclosed(idx))?;
+                    let (otherwise, end) = if self.tokens[next].keyword(self.source) == "else" {
+                        let (otherwise, end) = self.parse_nodes(&["endfor"])?;
+                        (
+                            Some((next, otherwise)),
+                            end.ok_or_else(|| self.unclosed(idx))?,
+                        )
+                    } else {
+                        (None, next)
+                    };
+                    Node::For {
+                        start: idx,
+                        body,
+                        otherwise,
+                        end,
+                    }
+                }
+                "macro" => {
+                    let (_, end) = self.parse_nodes(&["endmacro"])?;
+                    let end = end.ok_or_else(|| self.unclosed(idx))?;
+                    Node::Macro { start: idx, end }
+                }
+                "set" if !token.content(self.source).contains('=') => {
+                    let (body, end) = self.parse_nodes(&["endset"])?;
+                    let end = end.ok_or_else(|| self.unclosed(idx))?;
+                    Node::SetBlock {
+                        start: idx,
+                        body,
+                        end,
+                    }
+                }
+                "raw" => {
+                    let (body, end) = self.parse_nodes(&["endraw"])?;
+                    let end = end.ok_or_else(|| self.unclosed(idx))?;
+                    Node::Raw {
+                        start: idx,
+                        body,
+                        end,
+                    }
+                }
+                "set" | "do" => Node::Leaf(idx),
+                _ => {
+                    return Err(format!(
+                        "unsupported tag '{}' at position {}",
+                        token.content(self.source),
+                        token.tag.start
+                    ));
+                }
+            };
+            nodes.push(node);
+        }
+
+        Ok((nodes, None))
+    }
+
+    fn unclosed(&self, idx: usize) -> String {
+        format!(
+            "unclosed tag '{}' at position {}",
+            self.tokens[idx].content(self.source),
+            self.tokens[idx].tag.start
+        )
+    }
+}
+
+struct Renderer<'a> {
+    env: &'a Environment<'a>,
+    source: &'a str,
+    tokens: &'a [Token],
+    macros: &'a MacroLibrary,
+    scopes: Vec<BTreeMap<String, JinjaValue>>,
+    templated: String,
+    sliced: Vec<TemplatedFileSlice>,
+}
+
+impl<'a> Renderer<'a> {
+    fn render_nodes(&mut self, nodes: &[Node]) -> Result<(), String> {
+        for node in nodes {
+            self.render_node(node)?;
+        }
+        Ok(())
+    }
+
+    fn render_node(&mut self, node: &Node) -> Result<(), String> {
+        match node {
+            &Node::Leaf(idx) => self.render_leaf(idx),
+            Node::If { branches, end } => {
+                for (tag, body) in branches {
+                    self.record(*tag, "");
+                    let token = &self.tokens[*tag];
+                    let condition = match token.keyword(self.source) {
+                        "else" => None,
+                        keyword => Some(token.content(self.source)[keyword.len()..].trim()),
+                    };
+                    let taken = match condition {
+                        None => true,
+                        Some(condition) => {
+                            let snippet = format!("{{% if {condition} %}}1{{% endif %}}");
+                            self.render_snippet(condition, &snippet)? == "1"
+                        }
+                    };
+                    if taken {
+                        self.render_nodes(body)?;
+                        break;
+                    }
+                }
+                self.record(*end, "");
+                Ok(())
+            }
+            Node::For {
+                start,
+                body,
+                otherwise,
+                end,
+            } => {
+                self.record(*start, "");
+                let content = self.tokens[*start].content(self.source)["for".len()..].trim();
+                let (targets, iterable) = content
+                    .split_once(" in ")
+                    .ok_or_else(|| format!("malformed for loop '{content}'"))?;
+                let targets = targets.split(',').map(str::trim).collect::<Vec<_>>();
+                let iterable = iterable.trim();
+                let iterable = iterable.strip_suffix(" recursive").unwrap_or(iterable);
+
+                // `for x in items if condition` only loops over the items for
+                // which the condition holds.
+                let (iterable, condition) = match iterable.split_once(" if ") {
+                    Some((iterable, condition)) => (iterable.trim(), Some(condition.trim())),
+                    None => (iterable, None),
+                };
+
+                let mut items = Vec::new();
+                for item in self
+                    .eval(iterable)?
+                    .try_iter()
+                    .map_err(|err| err.to_string())?
+                {
+                    if let Some(condition) = condition {
+                        let snippet = format!("{{% if {condition} %}}1{{% endif %}}");
+                        self.scopes.push(Self::bind(&targets, item.clone())?);
+                        let taken = self.render_snippet(condition, &snippet);
+                        self.scopes.pop();
+                        if taken? != "1" {
+                            continue;
+                        }
+                    }
+                    items.push(item);
+                }
+                let length = items.len();
+
+                for (index, item) in items.into_iter().enumerate() {
+                    let mut scope = Self::bind(&targets, item)?;
+                    scope.insert(
+                        "loop".to_owned(),
+                        JinjaValue::from_iter([
+                            ("index", JinjaValue::from(index + 1)),
+                            ("index0", JinjaValue::from(index)),
+                            ("revindex", JinjaValue::from(length - index)),
+                            ("revindex0", JinjaValue::from(length - index - 1)),
+                            ("first", JinjaValue::from(index == 0)),
+                            ("last", JinjaValue::from(index + 1 == length)),
+                            ("length", JinjaValue::from(length)),
+                        ]),
+                    );
+
+                    self.scopes.push(scope);
+                    let result = self.render_nodes(body);
+                    self.scopes.pop();
+                    result?;
+                }
+
+                if length == 0 {
+                    if let Some((tag, otherwise)) = otherwise {
+                        self.record(*tag, "");
+                        self.render_nodes(otherwise)?;
+                    }
+                }
+                self.record(*end, "");
+                Ok(())
+            }
+            Node::SetBlock { start, body, end } => {
+                self.record(*start, "");
+                let target = self.tokens[*start].content(self.source)["set".len()..]
+                    .trim()
+                    .to_owned();
+
+                let templated = std::mem::take(&mut self.templated);
+                let sliced = std::mem::take(&mut self.sliced);
+                let result = self.render_nodes(body);
+                let value = std::mem::replace(&mut self.templated, templated);
+                let body_sliced = std::mem::replace(&mut self.sliced, sliced);
+                result?;
+
+                // The body renders into the variable rather than the output,
+                // but its source still has to be covered by slices.
+                let pos = self.templated.len();
+                self.sliced.extend(body_sliced.into_iter().map(|slice| {
+                    let slice_type = match slice.slice_type.as_str() {
+                        "literal" => "templated",
+                        slice_type => slice_type,
+                    };
+                    TemplatedFileSlice::new(slice_type, slice.source_slice, pos..pos)
+                }));
+
+                self.assign(target, JinjaValue::from(value));
+                self.record(*end, "");
+                Ok(())
+            }
+            Node::Macro { start, end } => {
+                self.record(*start, "");
+                self.record(*end, "");
+                Ok(())
+            }
+            Node::Raw { start, body, end } => {
+                self.record(*start, "");
+                self.render_nodes(body)?;
+                self.record(*end, "");
+                Ok(())
+            }
+        }
+    }
+
+    fn render_leaf(&mut self, idx: usize) -> Result<(), String> {
+        let token = &self.tokens[idx];
+        match token.kind {
+            TokenKind::Literal => self.record(idx, &self.source[token.span.clone()]),
+            TokenKind::Comment => self.record(idx, ""),
+            TokenKind::Expression => {
+                let expr = token.content(self.source);
+                let rendered = self.render_snippet(expr, &format!("{{{{ {expr} }}}}"))?;
+                self.record(idx, &rendered);
+            }
+            TokenKind::Statement => {
+                let keyword = token.keyword(self.source);
+                let content = token.content(self.source)[keyword.len()..].trim();
+                if keyword == "set" {
+                    let (target, expr) = content
+                        .split_once('=')
+                        .ok_or_else(|| format!("malformed set statement '{content}'"))?;
+                    let value = self.eval(expr.trim())?;
+                    self.assign(target.trim().to_owned(), value);
+                } else {
+                    self.eval(content)?;
+                }
+                self.record(idx, "");
+            }
+        }
+        Ok(())
+    }
+
+    /// Append rendered output for a token to the templated string.
+    fn record(&mut self, idx: usize, output: &str) {
+        let token = &self.tokens[idx];
+        let start = self.templated.len();
+        self.templated.push_str(output);
+        self.sliced.push(TemplatedFileSlice::new(
+            token.slice_type(self.source),
+            token.span.clone(),
+            start..self.templated.len(),
+        ));
+    }
+
+    /// Bind the loop targets to an item, unpacking it if there are several.
+    fn bind(targets: &[&str], item: JinjaValue) -> Result<BTreeMap<String, JinjaValue>, String> {
+        let mut scope = BTreeMap::new();
+        if let [target] = targets {
+            scope.insert((*target).to_owned(), item);
+        } else {
+            for (i, target) in targets.iter().enumerate() {
+                let value = item.get_item_by_index(i).map_err(|err| err.to_string())?;
+                scope.insert((*target).to_owned(), value);
+            }
+        }
+        Ok(scope)
+    }
+
+    fn assign(&mut self, target: String, value: JinjaValue) {
+        self.scopes.last_mut().unwrap().insert(target, value);
+    }
+
+    /// Build the context for rendering `expr`, falling back to the name of any
+    /// variable which is not otherwise defined.
+    fn context(&self, expr: &'a str) -> Result<BTreeMap<String, JinjaValue>, String> {
+        let mut context: BTreeMap<String, JinjaValue> = self
+            .scopes
+            .iter()
+            .flat_map(|scope| scope.iter())
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+
+        let undeclared = self.undeclared_variables(expr)?;
+        let state = self.env.empty_state();
+        for name in undeclared {
+            if !context.contains_key(&name)
+                && !self.macros.names.contains(&name)
+                && state.lookup(&name).is_none()
+            {
+                context.insert(name.clone(), JinjaValue::from(name));
+            }
+        }
+
+        Ok(context)
+    }
+
+    fn undeclared_variables(&self, expr: &str) -> Result<HashSet<String>, String> {
+        Ok(self
+            .env
+            .compile_expression(expr)
+            .map_err(|err| err.to_string())?
+            .undeclared_variables(false))
+    }
+
+    /// Evaluate an expression to a value.
+    fn eval(&self, expr: &'a str) -> Result<JinjaValue, String> {
+        let context = self.context(expr)?;
+        self.env
+            .compile_expression(expr)
+            .and_then(|compiled| compiled.eval(context))
+            .map_err(|err| err.to_string())
+    }
+
+    /// Render a snippet of Jinja which uses `expr`, with all known macros
+    /// available.
+    fn render_snippet(&self, expr: &'a str, snippet: &str) -> Result<String, String> {
+        let context = self.context(expr)?;
+
+        let mut used = self
+            .undeclared_variables(expr)?
+            .into_iter()
+            .filter(|name| self.macros.names.contains(name))
+            .collect::<Vec<_>>();
+        let source = if used.is_empty() {
+            snippet.to_owned()
+        } else {
+            used.sort();
+            format!(
+                "{{% from {MACROS_TEMPLATE:?} import {} %}}{snippet}",
+                used.join(", ")
+            )
+        };
+
+        self.env
+            .render_str(&source, context)
+            .map_err(|err| err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(in_str: &str, config: &str) -> TemplatedFile {
+        let config = FluffConfig::from_source(config);
+        JinjaTemplater
+            .process(in_str, "test.sql", Some(&config), &None)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_templater_jinja_simple() {
+        let templated_file = render(
+            "SELECT {{ col }} FROM {{ tbl }}",
+            "[sqruff:templater:jinja:context]\ncol = a\ntbl = my_table\n",
+        );
+
+        assert_eq!(templated_file.templated(), "SELECT a FROM my_table");
+        assert_eq!(
+            templated_file.sliced_file,
+            vec![
+                TemplatedFileSlice::new("literal", 0..7, 0..7),
+                TemplatedFileSlice::new("templated", 7..16, 7..8),
+                TemplatedFileSlice::new("literal", 16..22, 8..14),
+                TemplatedFileSlice::new("templated", 22..31, 14..22),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_templater_jinja_undefined_variable() {
+        let templated_file = render("SELECT * FROM {{ my_table }}", "");
+
+        assert_eq!(templated_file.templated(), "SELECT * FROM my_table");
+    }
+
+    #[test]
+    fn test_templater_jinja_if() {
+        let in_str = "SELECT\n{% if flag %}a{% elif other %}b{% else %}c{% endif %}\n";
+
+        let templated_file = render(in_str, "[sqruff:templater:jinja:context]\nflag = True\n");
+        assert_eq!(templated_file.templated(), "SELECT\na\n");
+
+        let templated_file = render(
+            in_str,
+            "[sqruff:templater:jinja:context]\nflag = False\nother = False\n",
+        );
+        assert_eq!(templated_file.templated(), "SELECT\nc\n");
+        assert_eq!(
+            templated_file.sliced_file,
+            vec![
+                TemplatedFileSlice::new("literal", 0..7, 0..7),
+                TemplatedFileSlice::new("block_start", 7..20, 7..7),
+                TemplatedFileSlice::new("block_mid", 21..37, 7..7),
+                TemplatedFileSlice::new("block_mid", 38..48, 7..7),
+                TemplatedFileSlice::new("literal", 48..49, 7..8),
+                TemplatedFileSlice::new("block_end", 49..60, 8..8),
+                TemplatedFileSlice::new("literal", 60..61, 8..9),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_templater_jinja_for() {
+        let templated_file = render(
+            "SELECT\n{% for col in cols %}{{ col }}{% if not loop.last %}, {% endif %}{% endfor %}\nFROM t",
+            "[sqruff:templater:jinja:context]\ncols = ['a', 'b', 'c']\n",
+        );
+
+        assert_eq!(templated_file.templated(), "SELECT\na, b, c\nFROM t");
+    }
+
+    #[test]
+    fn test_templater_jinja_whitespace_control() {
+        let templated_file = render("SELECT\n    {%- if true %}\n1\n{%- endif %}", "");
+
+        assert_eq!(templated_file.templated(), "SELECT\n1");
+        assert_eq!(
+            templated_file.sliced_file,
+            vec![
+                TemplatedFileSlice::new("literal", 0..6, 0..6),
+                TemplatedFileSlice::new("block_start", 6..25, 6..6),
+                TemplatedFileSlice::new("literal", 25..27, 6..8),
+                TemplatedFileSlice::new("block_end", 27..40, 8..8),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_templater_jinja_macro_and_set() {
+        let templated_file = render(
+            "{% macro cents(col) %}({{ col }} / 100){% endmacro %}\
+             {% set tbl = 'payments' %}\
+             SELECT {{ cents('amount') }} FROM {{ tbl }}{# done #}",
+            "",
+        );
+
+        assert_eq!(
+            templated_file.templated(),
+            "SELECT (amount / 100) FROM payments"
+        );
+    }
+
+    #[test]
+    fn test_templater_jinja_macro_calls_macro() {
+        let templated_file = render(
+            "{% macro total(col) %}{{ cents(col) }} + {{ tax }}{% endmacro %}\
+             {% macro cents(col) %}({{ col }} / 100){% endmacro %}\
+             SELECT {{ total('amount') }}{% if cents('x') %} FROM t{% endif %}",
+            "[sqruff:templater:jinja:context]\ntax = 1\n",
+        );
+
+        assert_eq!(
+            templated_file.templated(),
+            "SELECT (amount / 100) + 1 FROM t"
+        );
+    }
+
+    #[test]
+    fn test_templater_jinja_for_if() {
+        let templated_file = render(
+            "SELECT {% for col in ['a', 'b', 'c'] if col != 'b' %}{{ col }}{% if not loop.last %}, {% endif %}{% endfor %}",
+            "",
+        );
+
+        assert_eq!(templated_file.templated(), "SELECT a, c");
+    }
+
+    #[test]
+    fn test_templater_jinja_set_block() {
+        let in_str = "{% set cols %}a, b{% endset %}SELECT {{ cols }}";
+        let templated_file = render(in_str, "");
+        assert_eq!(templated_file.templated(), "SELECT a, b");
+
+        // The source of the block is covered without gaps.
+        let mut end = 0;
+        for slice in &templated_file.sliced_file {
+            assert_eq!(slice.source_slice.start, end);
+            end = slice.source_slice.end;
+        }
+        assert_eq!(end, in_str.len());
+    }
+
+    #[test]
+    fn test_templater_jinja_dbt_builtins() {
+        let in_str = "SELECT * FROM {{ ref('orders') }} JOIN {{ source('raw', 'customers') }}";
+
+        let templated_file = render(in_str, "");
+        assert_eq!(
+            templated_file.templated(),
+            "SELECT * FROM orders JOIN raw_customers"
+        );
+
+        let config =
+            FluffConfig::from_source("[sqruff:templater:jinja]\napply_dbt_builtins = False\n");
+        assert!(JinjaTemplater
+            .process(in_str, "test.sql", Some(&config), &None)
+            .is_err());
+    }
+
+    #[test]
+    fn test_templater_jinja_raw_slices() {
+        let in_str = "SELECT {# comment #}1{% raw %}{{ x }}{% endraw %}";
+        let templated_file = render(in_str, "");
+
+        assert_eq!(templated_file.templated(), "SELECT 1{{ x }}");
+        assert_eq!(
+            templated_file
+                .source_only_slices()
+                .iter()
+                .map(|slice| slice.source_slice())
+                .collect::<Vec<_>>(),
+            vec![7..20, 21..30, 37..49]
+        );
+    }
+
+    #[test]
+    fn test_templater_jinja_errors() {
+        for in_str in [
+            "SELECT {{ 1",
+            "{% if x %}SELECT 1",
+            "SELECT 1{% endif %}",
+            "{% include 'x.sql' %}",
+        ] {
+            assert!(JinjaTemplater
+                .process(in_str, "test.sql", None, &None)
+                .is_err());
+        }
+    }
+}
@@ -0,0 +1 @@
+select a::int from t
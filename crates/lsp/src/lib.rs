@@ -1,22 +1,38 @@
+mod worker;
+
+use std::collections::VecDeque;
+use std::ops::Range;
+
 use ahash::AHashMap;
-use lsp_server::{Connection, Message, Request, RequestId, Response};
+use lsp_server::{Connection, ErrorCode, Message, Request, RequestId, Response};
 use lsp_types::notification::{
-    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument,
+    Cancel, DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument,
     Notification, PublishDiagnostics,
 };
-use lsp_types::request::{Formatting, Request as _};
+use lsp_types::request::{
+    CodeActionRequest, CodeActionResolveRequest, Formatting, OnTypeFormatting, RangeFormatting,
+    Request as _,
+};
 use lsp_types::{
-    Diagnostic, DiagnosticSeverity, DidChangeTextDocumentParams, DidCloseTextDocumentParams,
+    CancelParams, CodeAction, CodeActionKind, CodeActionOptions, CodeActionOrCommand,
+    CodeActionParams, CodeActionProviderCapability, CodeDescription, Diagnostic,
+    DiagnosticSeverity, DiagnosticTag, DidChangeTextDocumentParams, DidCloseTextDocumentParams,
     DidOpenTextDocumentParams, DidSaveTextDocumentParams, DocumentFormattingParams,
+    DocumentOnTypeFormattingOptions, DocumentOnTypeFormattingParams, DocumentRangeFormattingParams,
     InitializeParams, InitializeResult, NumberOrString, OneOf, Position, PublishDiagnosticsParams,
     Registration, ServerCapabilities, TextDocumentIdentifier, TextDocumentItem,
-    TextDocumentSyncCapability, TextDocumentSyncKind, Uri, VersionedTextDocumentIdentifier,
+    TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Uri,
+    VersionedTextDocumentIdentifier, WorkspaceEdit,
 };
 use serde_json::Value;
+use similar::{DiffTag, TextDiff};
 use sqruff_lib::core::config::FluffConfig;
 use sqruff_lib::core::linter::core::Linter;
+use sqruff_lib_core::errors::SQLBaseError;
 use wasm_bindgen::prelude::*;
 
+use crate::worker::LintWorker;
+
 #[cfg(not(target_arch = "wasm32"))]
 fn load_config() -> FluffConfig {
     FluffConfig::from_root(None, false, None).unwrap_or_default()
@@ -30,18 +46,44 @@ fn load_config() -> FluffConfig {
 fn server_initialize_result() -> InitializeResult {
     InitializeResult {
         capabilities: ServerCapabilities {
-            text_document_sync: TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL).into(),
+            text_document_sync: TextDocumentSyncCapability::Kind(TextDocumentSyncKind::INCREMENTAL)
+                .into(),
             document_formatting_provider: OneOf::Left(true).into(),
+            document_range_formatting_provider: OneOf::Left(true).into(),
+            document_on_type_formatting_provider: DocumentOnTypeFormattingOptions {
+                first_trigger_character: ";".to_owned(),
+                more_trigger_character: None,
+            }
+            .into(),
+            code_action_provider: CodeActionProviderCapability::Options(CodeActionOptions {
+                code_action_kinds: Some(vec![CodeActionKind::QUICKFIX, fix_all_kind()]),
+                resolve_provider: Some(true),
+                ..Default::default()
+            })
+            .into(),
             ..Default::default()
         },
         server_info: None,
     }
 }
 
+/// Rules flagging code which can be removed, shown faded out by editors.
+const UNNECESSARY_RULES: &[&str] = &["AL05", "ST03"];
+
+const RULES_DOCS: &str = "https://github.com/quarylabs/sqruff/blob/main/docs/rules.md";
+
+/// The kind of the action fixing every violation of a document.
+fn fix_all_kind() -> CodeActionKind {
+    CodeActionKind::new("source.fixAll.sqruff")
+}
+
 pub struct LanguageServer {
     linter: Linter,
     send_diagnostics_callback: Box<dyn Fn(PublishDiagnosticsParams)>,
     documents: AHashMap<Uri, String>,
+    /// Lints documents in the background when running as a standalone server,
+    /// instead of on every change.
+    worker: Option<LintWorker>,
 }
 
 #[wasm_bindgen]
@@ -91,6 +133,20 @@ impl Wasm {
         let edits = self.0.format(uri);
         serde_wasm_bindgen::to_value(&edits).unwrap()
     }
+
+    #[wasm_bindgen(js_name = codeActions)]
+    pub fn code_actions(&self, params: JsValue) -> JsValue {
+        let params = serde_wasm_bindgen::from_value(params).unwrap();
+        let actions = self.0.code_actions(params);
+        serde_wasm_bindgen::to_value(&actions).unwrap()
+    }
+
+    #[wasm_bindgen(js_name = resolveCodeAction)]
+    pub fn resolve_code_action(&self, action: JsValue) -> JsValue {
+        let action = serde_wasm_bindgen::from_value(action).unwrap();
+        let action = self.0.resolve_code_action(action);
+        serde_wasm_bindgen::to_value(&action).unwrap()
+    }
 }
 
 impl LanguageServer {
@@ -99,9 +155,22 @@ impl LanguageServer {
             linter: Linter::new(load_config(), None, None),
             send_diagnostics_callback: Box::new(send_diagnostics_callback),
             documents: AHashMap::new(),
+            worker: None,
         }
     }
 
+    /// A server which lints documents on a background thread.
+    pub fn with_worker(
+        send_diagnostics_callback: impl Fn(PublishDiagnosticsParams) + Clone + Send + 'static,
+    ) -> Self {
+        let mut server = Self::new(send_diagnostics_callback.clone());
+        server.worker = Some(LintWorker::spawn(
+            server.linter.config().clone(),
+            send_diagnostics_callback,
+        ));
+        server
+    }
+
     fn on_request(&mut self, id: RequestId, method: &str, params: Value) -> Option<Response> {
         match method {
             Formatting::METHOD => {
@@ -113,29 +182,197 @@ impl LanguageServer {
                 let edits = self.format(uri);
                 Some(Response::new_ok(id, edits))
             }
+            RangeFormatting::METHOD => {
+                let DocumentRangeFormattingParams {
+                    text_document: TextDocumentIdentifier { uri },
+                    range,
+                    ..
+                } = serde_json::from_value(params).unwrap();
+
+                let edits = self.format_range(uri, range);
+                Some(Response::new_ok(id, edits))
+            }
+            OnTypeFormatting::METHOD => {
+                let DocumentOnTypeFormattingParams {
+                    text_document_position,
+                    ch,
+                    ..
+                } = serde_json::from_value(params).unwrap();
+
+                let edits = self.format_on_type(
+                    text_document_position.text_document.uri,
+                    text_document_position.position,
+                    &ch,
+                );
+                Some(Response::new_ok(id, edits))
+            }
+            CodeActionRequest::METHOD => {
+                let params: CodeActionParams = serde_json::from_value(params).unwrap();
+
+                let actions = self.code_actions(params);
+                Some(Response::new_ok(id, actions))
+            }
+            CodeActionResolveRequest::METHOD => {
+                let action: CodeAction = serde_json::from_value(params).unwrap();
+
+                let action = self.resolve_code_action(action);
+                Some(Response::new_ok(id, action))
+            }
             _ => None,
         }
     }
 
-    fn format(&mut self, uri: Uri) -> Vec<lsp_types::TextEdit> {
+    fn format(&self, uri: Uri) -> Vec<TextEdit> {
+        self.fix_edits(&self.documents[&uri], None)
+    }
+
+    fn format_range(&self, uri: Uri, range: lsp_types::Range) -> Vec<TextEdit> {
+        self.fix_edits(&self.documents[&uri], Some(range))
+    }
+
+    /// Format the statement ended by typing `ch`.
+    fn format_on_type(&self, uri: Uri, position: Position, ch: &str) -> Vec<TextEdit> {
         let text = &self.documents[&uri];
-        let tree = self.linter.lint_string(text, None, true);
+        let end = offset_at(text, position);
+        let start = text[..end.saturating_sub(ch.len())]
+            .rfind(';')
+            .map_or(0, |index| index + 1);
+
+        self.fix_edits(
+            text,
+            Some(lsp_types::Range::new(position_at(text, start), position)),
+        )
+    }
+
+    /// Edits applying the fixes which lie within `range`, or anywhere in
+    /// `text`. Only the words changed by a fix are replaced, so the rest of
+    /// the document stays untouched.
+    fn fix_edits(&self, text: &str, range: Option<lsp_types::Range>) -> Vec<TextEdit> {
+        // Offsets reported by the linter are into the text with normalised
+        // newlines. Its lines are those of the document, so positions in it
+        // are positions in the document.
+        let newline = if text.contains("\r\n") { "\r\n" } else { "\n" };
+        let text = Linter::normalise_newlines(text);
+        let range = range.map_or(0..text.len(), |range| {
+            offset_at(&text, range.start)..offset_at(&text, range.end)
+        });
+
+        self.linter
+            .lint_string(&text, None, true)
+            .source_patches()
+            .into_iter()
+            .flat_map(|patch| {
+                let source = &text[patch.source_slice.clone()];
+                changed_words(source, &patch.fixed_raw)
+                    .into_iter()
+                    .map(move |(slice, new_text)| {
+                        let start = patch.source_slice.start;
+                        (start + slice.start..start + slice.end, new_text)
+                    })
+            })
+            .filter(|(slice, _)| range.start <= slice.start && slice.end <= range.end)
+            .map(|(slice, new_text)| TextEdit {
+                range: lsp_types::Range::new(
+                    position_at(&text, slice.start),
+                    position_at(&text, slice.end),
+                ),
+                new_text: new_text.replace('\n', newline),
+            })
+            .collect()
+    }
 
-        let new_text = tree.fix_string();
-        let start_position = Position {
-            line: 0,
-            character: 0,
+    fn code_actions(&self, params: CodeActionParams) -> Vec<CodeActionOrCommand> {
+        let uri = params.text_document.uri;
+        let Some(text) = self.documents.get(&uri) else {
+            return Vec::new();
         };
-        let end_position = Position {
-            line: new_text.lines().count() as u32,
-            character: new_text.chars().count() as u32,
+
+        // Kinds are hierarchical, so asking for `source` includes `source.fixAll.sqruff`.
+        let wants = |kind: &CodeActionKind| {
+            params.context.only.as_ref().map_or(true, |only| {
+                only.iter()
+                    .any(|requested| kind.as_str().starts_with(requested.as_str()))
+            })
         };
 
-        let result = vec![lsp_types::TextEdit {
-            range: lsp_types::Range::new(start_position, end_position),
-            new_text,
-        }];
-        result
+        let mut actions = Vec::new();
+
+        if wants(&CodeActionKind::QUICKFIX) {
+            let mut disabled = Vec::new();
+
+            // The diagnostics come from the latest lint of the document, which
+            // already worked out their fixes.
+            for diagnostic in params.context.diagnostics {
+                let Some(NumberOrString::String(code)) = diagnostic.code.clone() else {
+                    continue;
+                };
+                if diagnostic.source.as_deref() != Some("sqruff") {
+                    continue;
+                }
+
+                let edits: Vec<TextEdit> = diagnostic
+                    .data
+                    .clone()
+                    .and_then(|data| serde_json::from_value(data).ok())
+                    .unwrap_or_default();
+                if !edits.is_empty() {
+                    actions.push(CodeAction {
+                        title: format!("Fix {code}: {}", diagnostic.message),
+                        kind: Some(CodeActionKind::QUICKFIX),
+                        diagnostics: Some(vec![diagnostic.clone()]),
+                        edit: Some(workspace_edit(&uri, edits)),
+                        is_preferred: Some(true),
+                        ..Default::default()
+                    });
+                }
+
+                // Offer to disable each rule once per line.
+                let line_no = diagnostic.range.start.line as usize + 1;
+                if disabled.contains(&(line_no, code.clone())) {
+                    continue;
+                }
+
+                if let Some(edit) = noqa_edit(text, line_no, &code) {
+                    actions.push(CodeAction {
+                        title: format!("Disable {code} for this line"),
+                        kind: Some(CodeActionKind::QUICKFIX),
+                        diagnostics: Some(vec![diagnostic]),
+                        edit: Some(workspace_edit(&uri, vec![edit])),
+                        ..Default::default()
+                    });
+                }
+                disabled.push((line_no, code));
+            }
+        }
+
+        // Fixing the whole document takes another lint, so the edits are only
+        // worked out once the action is picked.
+        if wants(&fix_all_kind()) {
+            actions.push(CodeAction {
+                title: "Fix all sqruff issues".to_owned(),
+                kind: Some(fix_all_kind()),
+                data: Some(serde_json::to_value(&uri).unwrap()),
+                ..Default::default()
+            });
+        }
+
+        actions
+            .into_iter()
+            .map(CodeActionOrCommand::CodeAction)
+            .collect()
+    }
+
+    /// Fill in the edits of the action fixing a whole document.
+    fn resolve_code_action(&self, mut action: CodeAction) -> CodeAction {
+        let uri: Option<Uri> = action
+            .data
+            .take()
+            .and_then(|data| serde_json::from_value(data).ok());
+
+        if let Some((uri, text)) = uri.and_then(|uri| self.documents.get_key_value(&uri)) {
+            action.edit = Some(workspace_edit(uri, self.fix_edits(text, None)));
+        }
+        action
     }
 
     pub fn on_notification(&mut self, method: &str, params: Value) {
@@ -154,16 +391,30 @@ impl LanguageServer {
             }
             DidChangeTextDocument::METHOD => {
                 let params: DidChangeTextDocumentParams = serde_json::from_value(params).unwrap();
-
-                let content = params.content_changes[0].text.clone();
                 let VersionedTextDocumentIdentifier { uri, version: _ } = params.text_document;
 
-                self.check_file(uri.clone(), &content);
-                self.documents.insert(uri, content);
+                let text = self.documents.entry(uri.clone()).or_default();
+                for change in params.content_changes {
+                    match change.range {
+                        Some(range) => {
+                            let start = offset_at(text, range.start);
+                            let end = offset_at(text, range.end).max(start);
+                            text.replace_range(start..end, &change.text);
+                        }
+                        None => *text = change.text,
+                    }
+                }
+
+                let text = text.clone();
+                self.check_file(uri, &text);
             }
             DidCloseTextDocument::METHOD => {
                 let params: DidCloseTextDocumentParams = serde_json::from_value(params).unwrap();
                 self.documents.remove(&params.text_document.uri);
+
+                if let Some(worker) = &self.worker {
+                    worker.close(params.text_document.uri);
+                }
             }
             DidSaveTextDocument::METHOD => {
                 let params: DidSaveTextDocumentParams = serde_json::from_value(params).unwrap();
@@ -180,40 +431,30 @@ impl LanguageServer {
     }
 
     fn recheck_files(&mut self) {
+        if let Some(worker) = &self.worker {
+            worker.set_config(self.linter.config().clone());
+        }
+
         for (uri, text) in self.documents.iter() {
             self.check_file(uri.clone(), text);
         }
     }
 
     fn check_file(&self, uri: Uri, text: &str) {
-        let result = self.linter.lint_string(text, None, false);
+        if let Some(worker) = &self.worker {
+            worker.lint(uri, text.to_owned());
+            return;
+        }
+
+        // Offsets reported by the linter are into the text with normalised
+        // newlines, which has the same lines as the document.
+        let text = Linter::normalise_newlines(text);
+        let result = self.linter.lint_string(&text, None, false);
 
         let diagnostics = result
             .violations
-            .into_iter()
-            .map(|violation| {
-                let range = {
-                    let pos = Position::new(
-                        (violation.line_no as u32).saturating_sub(1),
-                        (violation.line_pos as u32).saturating_sub(1),
-                    );
-                    lsp_types::Range::new(pos, pos)
-                };
-
-                let code = violation
-                    .rule
-                    .map(|rule| NumberOrString::String(rule.code.to_string()));
-
-                Diagnostic::new(
-                    range,
-                    DiagnosticSeverity::WARNING.into(),
-                    code,
-                    Some("sqruff".to_string()),
-                    violation.description,
-                    None,
-                    None,
-                )
-            })
+            .iter()
+            .map(|violation| diagnostic(&text, violation))
             .collect();
 
         let diagnostics = PublishDiagnosticsParams::new(uri.clone(), diagnostics, None);
@@ -221,6 +462,168 @@ impl LanguageServer {
     }
 }
 
+fn diagnostic(text: &str, violation: &SQLBaseError) -> Diagnostic {
+    let range = if violation.source_slice.is_empty() {
+        let pos = Position::new(
+            (violation.line_no as u32).saturating_sub(1),
+            (violation.line_pos as u32).saturating_sub(1),
+        );
+        lsp_types::Range::new(pos, pos)
+    } else {
+        lsp_types::Range::new(
+            position_at(text, violation.source_slice.start),
+            position_at(text, violation.source_slice.end),
+        )
+    };
+
+    // Match the exit code of `sqruff lint`: lexing and parsing errors and rule
+    // violations fail it, the rules listed in `warnings` don't.
+    let severity = if violation.warning {
+        DiagnosticSeverity::WARNING
+    } else {
+        DiagnosticSeverity::ERROR
+    };
+
+    let rule = violation.rule.as_ref();
+    let code_description = rule.filter(|rule| rule.code != "NOQA").map(|rule| {
+        let anchor = rule.name.replace('.', "");
+        CodeDescription {
+            href: format!("{RULES_DOCS}#{anchor}").parse().unwrap(),
+        }
+    });
+    let tags = rule
+        .filter(|rule| UNNECESSARY_RULES.contains(&rule.code))
+        .map(|_| vec![DiagnosticTag::UNNECESSARY]);
+
+    // The fixes travel with the diagnostic, so quick fixes don't need another
+    // lint.
+    let fixes = violation
+        .fixes
+        .iter()
+        .map(|fix| TextEdit {
+            range: lsp_types::Range::new(
+                position_at(text, fix.source_slice.start),
+                position_at(text, fix.source_slice.end),
+            ),
+            new_text: fix.edit.clone(),
+        })
+        .collect::<Vec<_>>();
+    let data = (!fixes.is_empty()).then(|| serde_json::to_value(fixes).unwrap());
+
+    Diagnostic {
+        range,
+        severity: Some(severity),
+        code: rule.map(|rule| NumberOrString::String(rule.code.to_string())),
+        code_description,
+        source: Some("sqruff".to_string()),
+        message: violation.description.clone(),
+        tags,
+        data,
+        ..Default::default()
+    }
+}
+
+/// The position of a byte offset in `text`, with the column counted in UTF-16
+/// code units as LSP expects.
+fn position_at(text: &str, offset: usize) -> Position {
+    let before = text.get(..offset).unwrap_or(text);
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+
+    Position::new(
+        before.matches('\n').count() as u32,
+        before[line_start..].encode_utf16().count() as u32,
+    )
+}
+
+/// The byte offset of a position in `text`, the inverse of [`position_at`].
+/// Positions past the end of a line are clamped to it.
+fn offset_at(text: &str, position: Position) -> usize {
+    let line_start = match position.line.checked_sub(1) {
+        None => 0,
+        Some(index) => text
+            .match_indices('\n')
+            .nth(index as usize)
+            .map_or(text.len(), |(index, _)| index + 1),
+    };
+    let line = text[line_start..].split('\n').next().unwrap_or_default();
+
+    let mut column = 0;
+    for (index, char) in line.char_indices() {
+        if column >= position.character as usize {
+            return line_start + index;
+        }
+        column += char.len_utf16();
+    }
+
+    line_start + line.len()
+}
+
+/// The byte ranges of `old` which differ from `new`, with their replacements.
+/// Whole words and runs of whitespace are compared, which keeps the edits as
+/// small as the fixes.
+fn changed_words(old: &str, new: &str) -> Vec<(Range<usize>, String)> {
+    let diff = TextDiff::from_words(old, new);
+    let offsets = |slices: &[&str]| {
+        let mut offsets = vec![0];
+        offsets.extend(slices.iter().scan(0, |offset, slice| {
+            *offset += slice.len();
+            Some(*offset)
+        }));
+        offsets
+    };
+    let (old_offsets, new_offsets) = (offsets(diff.old_slices()), offsets(diff.new_slices()));
+
+    diff.ops()
+        .iter()
+        .filter(|op| op.tag() != DiffTag::Equal)
+        .map(|op| {
+            let (old_range, new_range) = (op.old_range(), op.new_range());
+            (
+                old_offsets[old_range.start]..old_offsets[old_range.end],
+                new[new_offsets[new_range.start]..new_offsets[new_range.end]].to_owned(),
+            )
+        })
+        .collect()
+}
+
+fn workspace_edit(uri: &Uri, edits: Vec<TextEdit>) -> WorkspaceEdit {
+    WorkspaceEdit {
+        changes: Some([(uri.clone(), edits)].into_iter().collect()),
+        ..Default::default()
+    }
+}
+
+/// An edit adding `code` to the `noqa` comment ending a line, or adding such a
+/// comment. `None` if the line ends in another comment or already ignores the
+/// rule.
+fn noqa_edit(text: &str, line_no: usize, code: &str) -> Option<TextEdit> {
+    let line = text.lines().nth(line_no.checked_sub(1)?)?.trim_end();
+
+    let new_text = match line.rfind("--") {
+        Some(index) => {
+            let rules = line[index + 2..]
+                .trim_start()
+                .strip_prefix("noqa")?
+                .trim_start()
+                .strip_prefix(':')?;
+            let rules = rules.split(',').map(str::trim).collect::<Vec<_>>();
+
+            // `noqa: disable=...` also applies to the lines which follow.
+            if rules.contains(&code) || rules[0].contains('=') {
+                return None;
+            }
+            format!(",{code}")
+        }
+        None => format!(" -- noqa: {code}"),
+    };
+
+    let position = Position::new(line_no as u32 - 1, line.encode_utf16().count() as u32);
+    Some(TextEdit {
+        range: lsp_types::Range::new(position, position),
+        new_text,
+    })
+}
+
 pub fn run() {
     let (connection, io_threads) = Connection::stdio();
     let (id, params) = connection.initialize_start().unwrap();
@@ -236,7 +639,7 @@ pub fn run() {
 
 fn main_loop(connection: Connection, _init_param: InitializeParams) {
     let sender = connection.sender.clone();
-    let mut lsp = LanguageServer::new(move |diagnostics| {
+    let mut lsp = LanguageServer::with_worker(move |diagnostics| {
         let notification = new_notification::<PublishDiagnostics>(diagnostics);
         sender.send(Message::Notification(notification)).unwrap();
     });
@@ -251,21 +654,52 @@ fn main_loop(connection: Connection, _init_param: InitializeParams) {
         )))
         .unwrap();
 
-    for message in &connection.receiver {
-        match message {
-            Message::Request(request) => {
-                if connection.handle_shutdown(&request).unwrap() {
-                    return;
+    let mut queue = VecDeque::new();
+
+    while let Ok(message) = connection.receiver.recv() {
+        // Take everything which arrived while the last message was handled,
+        // so requests cancelled in the meantime are never run.
+        queue.push_back(message);
+        queue.extend(connection.receiver.try_iter());
+
+        while let Some(message) = queue.pop_front() {
+            match message {
+                Message::Request(request) => {
+                    if connection.handle_shutdown(&request).unwrap() {
+                        return;
+                    }
+
+                    if let Some(response) =
+                        lsp.on_request(request.id, &request.method, request.params)
+                    {
+                        connection.sender.send(Message::Response(response)).unwrap();
+                    }
                 }
+                Message::Response(_) => {}
+                Message::Notification(notification) if notification.method == Cancel::METHOD => {
+                    let CancelParams { id } = serde_json::from_value(notification.params).unwrap();
+                    let id = match id {
+                        NumberOrString::Number(id) => RequestId::from(id),
+                        NumberOrString::String(id) => RequestId::from(id),
+                    };
+
+                    let cancelled = queue.iter().position(
+                        |message| matches!(message, Message::Request(request) if request.id == id),
+                    );
+                    if let Some(index) = cancelled {
+                        queue.remove(index);
 
-                if let Some(response) = lsp.on_request(request.id, &request.method, request.params)
-                {
-                    connection.sender.send(Message::Response(response)).unwrap();
+                        let response = Response::new_err(
+                            id,
+                            ErrorCode::RequestCanceled as i32,
+                            "Request cancelled".to_owned(),
+                        );
+                        connection.sender.send(Message::Response(response)).unwrap();
+                    }
+                }
+                Message::Notification(notification) => {
+                    lsp.on_notification(&notification.method, notification.params);
                 }
-            }
-            Message::Response(_) => {}
-            Message::Notification(notification) => {
-                lsp.on_notification(&notification.method, notification.params);
             }
         }
     }
@@ -310,3 +744,300 @@ where
         params: serde_json::to_value(&params).unwrap(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use lsp_types::notification::{DidChangeTextDocument, DidOpenTextDocument, Notification as _};
+    use lsp_types::{
+        CodeActionOrCommand, CodeActionParams, DiagnosticSeverity, DiagnosticTag, NumberOrString,
+        Position, Range, TextEdit, Uri,
+    };
+    use serde_json::json;
+    use sqruff_lib::core::config::FluffConfig;
+    use sqruff_lib_core::errors::{ErrorStructRule, SQLBaseError};
+
+    use super::{changed_words, diagnostic, noqa_edit, offset_at, position_at, LanguageServer};
+
+    #[test]
+    fn test_code_actions() {
+        let published = Rc::new(RefCell::new(Vec::new()));
+        let mut server = LanguageServer::new({
+            let published = published.clone();
+            move |diagnostics| published.borrow_mut().push(diagnostics)
+        });
+        *server.linter.config_mut() = FluffConfig::from_source("[sqruff]\nrules = LT01\n");
+
+        server.on_notification(
+            DidOpenTextDocument::METHOD,
+            json!({
+                "textDocument": {
+                    "uri": "file:///a.sql",
+                    "languageId": "sql",
+                    "version": 0,
+                    "text": "SELECT   1\n",
+                }
+            }),
+        );
+        let diagnostics = published.take().pop().unwrap().diagnostics;
+
+        let params: CodeActionParams = serde_json::from_value(json!({
+            "textDocument": { "uri": "file:///a.sql" },
+            "range": {
+                "start": { "line": 0, "character": 0 },
+                "end": { "line": 0, "character": 0 },
+            },
+            "context": { "diagnostics": diagnostics },
+        }))
+        .unwrap();
+        let uri = params.text_document.uri.clone();
+
+        let mut actions = server
+            .code_actions(params)
+            .into_iter()
+            .map(|action| match action {
+                CodeActionOrCommand::CodeAction(action) => action,
+                CodeActionOrCommand::Command(_) => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(actions.len(), 3);
+        assert!(actions[0].title.starts_with("Fix LT01: "));
+        assert_eq!(actions[1].title, "Disable LT01 for this line");
+        assert_eq!(actions[2].title, "Fix all sqruff issues");
+
+        // Fixing everything is only worked out when the action is resolved.
+        assert!(actions[2].edit.is_none());
+        actions[2] = server.resolve_code_action(actions[2].clone());
+
+        let edits = |index: usize| {
+            actions[index]
+                .edit
+                .as_ref()
+                .unwrap()
+                .changes
+                .as_ref()
+                .unwrap()[&uri]
+                .clone()
+        };
+        assert_eq!(
+            edits(0),
+            [TextEdit {
+                range: Range::new(Position::new(0, 6), Position::new(0, 9)),
+                new_text: " ".into(),
+            }]
+        );
+        assert_eq!(edits(1)[0].new_text, " -- noqa: LT01");
+        assert_eq!(edits(2), edits(0));
+    }
+
+    #[test]
+    fn test_format_range() {
+        let mut server = LanguageServer::new(|_| {});
+        *server.linter.config_mut() = FluffConfig::from_source("[sqruff]\nrules = LT01\n");
+
+        server.on_notification(
+            DidOpenTextDocument::METHOD,
+            json!({
+                "textDocument": {
+                    "uri": "file:///a.sql",
+                    "languageId": "sql",
+                    "version": 0,
+                    "text": "SELECT   1;\nSELECT   2;\n",
+                }
+            }),
+        );
+        let uri: Uri = "file:///a.sql".parse().unwrap();
+
+        let edit = |line| TextEdit {
+            range: Range::new(Position::new(line, 6), Position::new(line, 9)),
+            new_text: " ".into(),
+        };
+        assert_eq!(server.format(uri.clone()), [edit(0), edit(1)]);
+        assert_eq!(
+            server.format_range(
+                uri.clone(),
+                Range::new(Position::new(1, 0), Position::new(1, 11))
+            ),
+            [edit(1)]
+        );
+        assert_eq!(
+            server.format_on_type(uri, Position::new(0, 11), ";"),
+            [edit(0)]
+        );
+    }
+
+    #[test]
+    fn test_format_crlf() {
+        let mut server = LanguageServer::new(|_| {});
+        *server.linter.config_mut() = FluffConfig::from_source("[sqruff]\nrules = LT01\n");
+        let uri: Uri = "file:///a.sql".parse().unwrap();
+        server
+            .documents
+            .insert(uri.clone(), "SELECT 1\r\nFROM   t\r\n".into());
+
+        assert_eq!(
+            server.format(uri),
+            [TextEdit {
+                range: Range::new(Position::new(1, 4), Position::new(1, 7)),
+                new_text: " ".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_changed_words() {
+        assert_eq!(
+            changed_words("SELECT   a\nFROM  t\n", "SELECT a\nFROM t\n"),
+            [(6..9, " ".to_owned()), (15..17, " ".to_owned())]
+        );
+    }
+
+    #[test]
+    fn test_diagnostic() {
+        let text = "SELECT a\nFROM t AS unused\n";
+        let violation = SQLBaseError {
+            line_no: 2,
+            line_pos: 11,
+            description: "Alias is never used".into(),
+            rule: Some(ErrorStructRule {
+                name: "aliasing.unused",
+                code: "AL05",
+            }),
+            source_slice: 19..25,
+            ..SQLBaseError::default()
+        };
+
+        let result = diagnostic(text, &violation);
+        assert_eq!(
+            result.range,
+            Range::new(Position::new(1, 10), Position::new(1, 16))
+        );
+        assert_eq!(result.severity, Some(DiagnosticSeverity::ERROR));
+        assert_eq!(
+            result.code_description.unwrap().href.as_str(),
+            "https://github.com/quarylabs/sqruff/blob/main/docs/rules.md#aliasingunused"
+        );
+        assert_eq!(result.tags, Some(vec![DiagnosticTag::UNNECESSARY]));
+
+        let warning = SQLBaseError {
+            warning: true,
+            ..violation
+        };
+        let result = diagnostic(text, &warning);
+        assert_eq!(result.severity, Some(DiagnosticSeverity::WARNING));
+    }
+
+    #[test]
+    fn test_position_at() {
+        let text = "SELECT 'é'\nFROM t";
+
+        assert_eq!(position_at(text, 0), Position::new(0, 0));
+        assert_eq!(position_at(text, 11), Position::new(0, 10));
+        assert_eq!(position_at(text, 12), Position::new(1, 0));
+        assert_eq!(position_at(text, text.len()), Position::new(1, 6));
+    }
+
+    #[test]
+    fn test_offset_at() {
+        let text = "SELECT 'é'\nFROM t";
+
+        assert_eq!(offset_at(text, Position::new(0, 10)), 11);
+        assert_eq!(offset_at(text, Position::new(0, 40)), 11);
+        assert_eq!(offset_at(text, Position::new(1, 0)), 12);
+        assert_eq!(offset_at(text, Position::new(1, 6)), text.len());
+        assert_eq!(offset_at(text, Position::new(5, 0)), text.len());
+    }
+
+    #[test]
+    fn test_warnings() {
+        let published = Rc::new(RefCell::new(Vec::new()));
+        let mut server = LanguageServer::new({
+            let published = published.clone();
+            move |diagnostics| published.borrow_mut().push(diagnostics)
+        });
+        *server.linter.config_mut() =
+            FluffConfig::from_source("[sqruff]\nrules = LT01,LT12\nwarnings = LT01\n");
+
+        let uri: Uri = "file:///a.sql".parse().unwrap();
+        server.check_file(uri, "SELECT   1");
+
+        let severities = published.take()[0]
+            .diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.code.clone().unwrap(), diagnostic.severity))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            severities,
+            [
+                (
+                    NumberOrString::String("LT01".into()),
+                    Some(DiagnosticSeverity::WARNING)
+                ),
+                (
+                    NumberOrString::String("LT12".into()),
+                    Some(DiagnosticSeverity::ERROR)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_incremental_changes() {
+        let mut server = LanguageServer::new(|_| {});
+        server.on_notification(
+            DidOpenTextDocument::METHOD,
+            json!({
+                "textDocument": {
+                    "uri": "file:///a.sql",
+                    "languageId": "sql",
+                    "version": 0,
+                    "text": "SELECT a\nFROM t\n",
+                }
+            }),
+        );
+        server.on_notification(
+            DidChangeTextDocument::METHOD,
+            json!({
+                "textDocument": { "uri": "file:///a.sql", "version": 1 },
+                "contentChanges": [
+                    {
+                        "range": {
+                            "start": { "line": 0, "character": 7 },
+                            "end": { "line": 0, "character": 8 },
+                        },
+                        "text": "b, c",
+                    },
+                    {
+                        "range": {
+                            "start": { "line": 2, "character": 0 },
+                            "end": { "line": 2, "character": 0 },
+                        },
+                        "text": "WHERE c\n",
+                    },
+                ],
+            }),
+        );
+
+        let uri = "file:///a.sql".parse().unwrap();
+        assert_eq!(server.documents[&uri], "SELECT b, c\nFROM t\nWHERE c\n");
+    }
+
+    #[test]
+    fn test_noqa_edit() {
+        let new_text = |text, code| noqa_edit(text, 1, code).map(|edit| edit.new_text);
+
+        assert_eq!(
+            new_text("SELECT   1  ", "LT01").as_deref(),
+            Some(" -- noqa: LT01")
+        );
+        assert_eq!(
+            new_text("SELECT   a  -- noqa: AL01", "LT01").as_deref(),
+            Some(",LT01")
+        );
+        assert_eq!(new_text("SELECT a -- noqa: LT01, AL01", "LT01"), None);
+        assert_eq!(new_text("SELECT a -- noqa:disable=AL01", "LT01"), None);
+        assert_eq!(new_text("SELECT a -- the answer", "LT01"), None);
+    }
+}
@@ -0,0 +1,205 @@
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Duration;
+
+use ahash::AHashMap;
+use lsp_types::{PublishDiagnosticsParams, Uri};
+use sqruff_lib::core::config::FluffConfig;
+use sqruff_lib::core::linter::core::Linter;
+
+use crate::diagnostic;
+
+/// How long a document has to go without edits before it is linted.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+enum Job {
+    Lint { uri: Uri, text: String },
+    Close(Uri),
+    Config(Box<FluffConfig>),
+}
+
+/// Lints documents on a background thread, so the server keeps handling
+/// messages while the user is typing.
+///
+/// Documents are only linted once they haven't been edited for a while, and
+/// results for text which has since been edited are dropped.
+pub(crate) struct LintWorker {
+    sender: Sender<Job>,
+}
+
+impl LintWorker {
+    pub(crate) fn spawn(
+        config: FluffConfig,
+        send_diagnostics: impl Fn(PublishDiagnosticsParams) + Send + 'static,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || run(config, receiver, send_diagnostics));
+
+        Self { sender }
+    }
+
+    pub(crate) fn lint(&self, uri: Uri, text: String) {
+        _ = self.sender.send(Job::Lint { uri, text });
+    }
+
+    pub(crate) fn close(&self, uri: Uri) {
+        _ = self.sender.send(Job::Close(uri));
+    }
+
+    pub(crate) fn set_config(&self, config: FluffConfig) {
+        _ = self.sender.send(Job::Config(Box::new(config)));
+    }
+}
+
+struct State {
+    linter: Linter,
+    /// The text of documents waiting to be linted. A newer edit replaces the
+    /// text of an older one, which cancels linting the stale text.
+    pending: AHashMap<Uri, String>,
+    /// The generation of the latest edit of each open document.
+    generations: AHashMap<Uri, u64>,
+    generation: u64,
+}
+
+impl State {
+    /// Apply a job, returning whether it was an edit.
+    fn apply(&mut self, job: Job) -> bool {
+        match job {
+            Job::Lint { uri, text } => {
+                self.generation += 1;
+                self.generations.insert(uri.clone(), self.generation);
+                self.pending.insert(uri, text);
+                true
+            }
+            Job::Close(uri) => {
+                self.generations.remove(&uri);
+                self.pending.remove(&uri);
+                false
+            }
+            Job::Config(config) => {
+                self.linter = Linter::new(*config, None, None);
+                false
+            }
+        }
+    }
+
+    /// Lint the pending documents, until one of them is edited again.
+    fn lint_pending(
+        &mut self,
+        receiver: &Receiver<Job>,
+        send_diagnostics: &impl Fn(PublishDiagnosticsParams),
+    ) {
+        while let Some(uri) = self.pending.keys().next().cloned() {
+            let text = Linter::normalise_newlines(&self.pending.remove(&uri).unwrap()).into_owned();
+            let generation = self.generations[&uri];
+
+            let diagnostics = self
+                .linter
+                .lint_string(&text, None, false)
+                .violations
+                .iter()
+                .map(|violation| diagnostic(&text, violation))
+                .collect();
+
+            let mut edited = false;
+            for job in receiver.try_iter() {
+                edited |= self.apply(job);
+            }
+
+            if self.generations.get(&uri) == Some(&generation) {
+                send_diagnostics(PublishDiagnosticsParams::new(uri, diagnostics, None));
+            }
+            if edited {
+                break;
+            }
+        }
+    }
+}
+
+fn run(
+    config: FluffConfig,
+    receiver: Receiver<Job>,
+    send_diagnostics: impl Fn(PublishDiagnosticsParams),
+) {
+    let mut state = State {
+        linter: Linter::new(config, None, None),
+        pending: AHashMap::new(),
+        generations: AHashMap::new(),
+        generation: 0,
+    };
+
+    loop {
+        let job = if state.pending.is_empty() {
+            receiver.recv().map_err(|_| RecvTimeoutError::Disconnected)
+        } else {
+            receiver.recv_timeout(DEBOUNCE)
+        };
+
+        match job {
+            Ok(job) => {
+                state.apply(job);
+            }
+            // Nothing was edited for a while, so lint the pending documents.
+            Err(RecvTimeoutError::Timeout) => state.lint_pending(&receiver, &send_diagnostics),
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::sync::mpsc;
+
+    use ahash::AHashMap;
+    use lsp_types::Uri;
+    use sqruff_lib::core::config::FluffConfig;
+    use sqruff_lib::core::linter::core::Linter;
+
+    use super::{Job, State};
+
+    fn lint(uri: &Uri, text: &str) -> Job {
+        Job::Lint {
+            uri: uri.clone(),
+            text: text.into(),
+        }
+    }
+
+    #[test]
+    fn test_lint_worker() {
+        let config = FluffConfig::from_source("[sqruff]\nrules = LT01\n");
+        let mut state = State {
+            linter: Linter::new(config, None, None),
+            pending: AHashMap::new(),
+            generations: AHashMap::new(),
+            generation: 0,
+        };
+        let (sender, receiver) = mpsc::channel();
+        let published = RefCell::new(Vec::new());
+        let send_diagnostics = |diagnostics| published.borrow_mut().push(diagnostics);
+
+        let uri: Uri = "file:///a.sql".parse().unwrap();
+        state.apply(lint(&uri, "SELECT   1\n"));
+        state.apply(lint(&uri, "SELECT  1\n"));
+        state.apply(lint(&uri, "SELECT 1\n"));
+
+        // Only the latest text is linted.
+        state.lint_pending(&receiver, &send_diagnostics);
+        let diagnostics = published.take();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].uri, uri);
+        assert!(diagnostics[0].diagnostics.is_empty());
+
+        // Results for text edited while it was being linted are dropped.
+        state.apply(lint(&uri, "SELECT   1\n"));
+        sender.send(lint(&uri, "SELECT  1\n")).unwrap();
+        state.lint_pending(&receiver, &send_diagnostics);
+        assert!(published.take().is_empty());
+        assert_eq!(state.pending[&uri], "SELECT  1\n");
+
+        // Closed documents aren't linted.
+        state.apply(Job::Close(uri));
+        state.lint_pending(&receiver, &send_diagnostics);
+        assert!(published.take().is_empty());
+    }
+}